@@ -6,7 +6,7 @@ fn main() {
     let args: Vec<String> = env::args().collect();
     let word: Vec<char> = (&args[1]).chars().collect();
 
-    if word.len() % 2 == 1 {
+    if word.is_empty() || word.len() % 2 == 1 {
         println!("0");
         return;
     }
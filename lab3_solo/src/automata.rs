@@ -1,11 +1,17 @@
 #![allow(dead_code)]
 
 use std::any::Any;
-use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
 
 pub const EPSILON: &str = "";
 pub const START: usize = 0;
 
+pub type Symbol = char;
+
 pub trait Automata {
     fn as_any(&self) -> &dyn Any;
 
@@ -14,10 +20,25 @@ pub trait Automata {
     fn determinize(&self) -> Box<dyn Automata>;
 }
 
-#[derive(Debug, Clone, PartialEq)]
+// Все автоматы программы -- AutomataImpl, типаж лишь скрывает представление
+pub fn get_automata_impl(automata: &dyn Automata) -> &AutomataImpl {
+    automata.as_any().downcast_ref::<AutomataImpl>().unwrap()
+}
+
+// Порядок shortlex: сначала по длине, слова равной длины -- лексикографически
+pub fn compare_shortlex(first: &str, second: &str) -> Ordering {
+    first
+        .chars()
+        .count()
+        .cmp(&second.chars().count())
+        .then_with(|| first.cmp(second))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AutomataImpl {
     pub size: usize,
-    pub transitions: Vec<Vec<HashSet<String>>>,
+    // Метки упорядочены, чтобы вывод автомата не зависел от запуска
+    pub transitions: Vec<Vec<BTreeSet<String>>>,
     pub start_states: Vec<bool>,
     pub finite_states: Vec<bool>,
 }
@@ -28,27 +49,8 @@ impl Automata for AutomataImpl {
     }
 
     fn check_membership(&self, word: &str) -> bool {
-        let mut state = START;
-
-        for letter in word.chars() {
-            let letter = letter.to_string();
-
-            let mut next_state = std::usize::MAX;
-            for (i, labels) in self.transitions[state].iter().enumerate() {
-                if labels.contains(&letter) {
-                    next_state = i;
-                    break;
-                }
-            }
-
-            if next_state == std::usize::MAX {
-                return false;
-            }
-
-            state = next_state;
-        }
-
-        self.finite_states[state]
+        self.get_state(word)
+            .is_some_and(|state| self.finite_states[state])
     }
 
     fn determinize(&self) -> Box<dyn Automata> {
@@ -59,7 +61,8 @@ impl Automata for AutomataImpl {
             to: usize,
         }
 
-        let start_subset = BTreeSet::from([START]);
+        let start_subset: BTreeSet<usize> =
+            (0..self.size).filter(|state| self.start_states[*state]).collect();
         let mut subset_to_state = HashMap::from([(start_subset.to_owned(), START)]);
         let mut state_to_subset = HashMap::from([(START, start_subset)]);
         let mut state_counter = START + 1;
@@ -69,7 +72,7 @@ impl Automata for AutomataImpl {
         let mut transitions = HashSet::<Transition>::new();
 
         while let Some(state) = states_to_visit.pop_front() {
-            let mut label_to_subset = HashMap::<String, BTreeSet<usize>>::new();
+            let mut label_to_subset = BTreeMap::<String, BTreeSet<usize>>::new();
             let closure = self.get_epsilon_closure(state_to_subset.get(&state).unwrap());
 
             for closure_state in closure {
@@ -134,7 +137,7 @@ impl AutomataImpl {
         let mut start_states = vec![false; size];
         start_states[START] = true;
 
-        let transitions = vec![vec![HashSet::new(); size]; size];
+        let transitions = vec![vec![BTreeSet::new(); size]; size];
 
         let finite_states = vec![false; size];
 
@@ -146,6 +149,282 @@ impl AutomataImpl {
         }
     }
 
+    // Формат файла: строка `final <состояния>` и строки переходов
+    // `<из> <символ> <в>`, где символ `ε` обозначает ε-переход. Состояние 0
+    // является начальным, пустые строки и строки с `#` пропускаются.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut size = START + 1;
+        let mut finite_states = Vec::new();
+        let mut transitions = Vec::new();
+
+        let parse_state = |token: &str| {
+            token
+                .parse::<usize>()
+                .map_err(|_| format!("invalid state '{token}'"))
+        };
+
+        for line in text.lines() {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            match tokens.as_slice() {
+                [] => continue,
+                [comment, ..] if comment.starts_with('#') => continue,
+                ["final", states @ ..] => {
+                    for state in states {
+                        let state = parse_state(state)?;
+                        size = size.max(state + 1);
+                        finite_states.push(state);
+                    }
+                }
+                [from, label, to] => {
+                    let from = parse_state(from)?;
+                    let to = parse_state(to)?;
+                    let label = if label.eq(&"ε") { EPSILON } else { label };
+                    if label.chars().count() > 1 {
+                        return Err(format!("invalid label '{label}'"));
+                    }
+                    size = size.max(from + 1).max(to + 1);
+                    transitions.push((from, label.to_owned(), to));
+                }
+                _ => return Err(format!("invalid line '{line}'")),
+            }
+        }
+
+        let mut automata = Self::new(size);
+        for (from, label, to) in transitions {
+            automata.transitions[from][to].insert(label);
+        }
+        for state in finite_states {
+            automata.finite_states[state] = true;
+        }
+
+        Ok(automata)
+    }
+
+    // Запись в формате parse; начальным должно быть состояние 0
+    pub fn render_file(&self) -> String {
+        let finite_states = (0..self.size)
+            .filter(|state| self.finite_states[*state])
+            .map(|state| format!(" {state}"));
+        let mut text = format!("final{}\n", String::from_iter(finite_states));
+
+        for (from, row) in self.transitions.iter().enumerate() {
+            for (to, labels) in row.iter().enumerate() {
+                for label in labels {
+                    let label = if label == EPSILON { "ε" } else { label };
+                    text.push_str(&format!("{from} {label} {to}\n"));
+                }
+            }
+        }
+
+        text
+    }
+
+    // Кратчайшее слово, на котором расходятся два ДКА
+    pub fn find_distinguishing_word(&self, other: &Self, alphabet: &str) -> Option<String> {
+        let is_finite = |automata: &Self, state: Option<usize>| {
+            state.is_some_and(|state| automata.finite_states[state])
+        };
+        let get_next_state = |automata: &Self, state: Option<usize>, letter: char| {
+            state.and_then(|state| automata.get_next_state(state, letter))
+        };
+
+        let start = (Some(START), Some(START));
+        let mut visited = HashSet::from([start]);
+        let mut states_to_visit = VecDeque::from([(start, String::new())]);
+
+        while let Some(((first, second), word)) = states_to_visit.pop_front() {
+            if is_finite(self, first) != is_finite(other, second) {
+                return Some(word);
+            }
+
+            for letter in alphabet.chars() {
+                let next = (
+                    get_next_state(self, first, letter),
+                    get_next_state(other, second, letter),
+                );
+                if visited.insert(next) {
+                    states_to_visit.push_back((next, format!("{word}{letter}")));
+                }
+            }
+        }
+
+        None
+    }
+
+    // Таблица переходов: `->` отмечает начальное состояние, `*` -- заключительные
+    pub fn render_transitions(&self, alphabet: &str) -> String {
+        let mut rows = vec![Vec::from_iter(
+            std::iter::once(String::new()).chain(alphabet.chars().map(String::from)),
+        )];
+
+        for state in 0..self.size {
+            let start_marker = if self.start_states[state] { "->" } else { "  " };
+            let finite_marker = if self.finite_states[state] { "*" } else { " " };
+            let mut row = vec![format!("{start_marker}{finite_marker}{state}")];
+
+            for letter in alphabet.chars() {
+                let letter = letter.to_string();
+                let next_states: Vec<String> = self.transitions[state]
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, labels)| labels.contains(&letter))
+                    .map(|(next_state, _)| next_state.to_string())
+                    .collect();
+
+                row.push(match next_states.len() {
+                    0 => "-".to_string(),
+                    1 => next_states[0].to_owned(),
+                    _ => format!("{{{}}}", next_states.join(",")),
+                });
+            }
+            rows.push(row);
+        }
+
+        let widths: Vec<usize> = (0..rows[0].len())
+            .map(|column| rows.iter().map(|row| row[column].chars().count()).max().unwrap())
+            .collect();
+
+        let mut table = String::new();
+        for row in rows {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect();
+            table.push_str(cells.join("  ").trim_end());
+            table.push('\n');
+        }
+
+        table
+    }
+
+    // Состояние ДКА, в которое ведёт слово
+    pub fn get_state(&self, word: &str) -> Option<usize> {
+        let mut state = START;
+        for letter in word.chars() {
+            state = self.get_next_state(state, letter)?;
+        }
+        Some(state)
+    }
+
+    // Кратчайшие в shortlex-порядке слова, ведущие в состояния ДКА
+    pub fn get_access_words(&self, alphabet: &str) -> Vec<Option<String>> {
+        let mut access_words = vec![None; self.size];
+        access_words[START] = Some(String::new());
+        let mut states_to_visit = VecDeque::from([START]);
+
+        while let Some(state) = states_to_visit.pop_front() {
+            let word = access_words[state].to_owned().unwrap();
+            for letter in alphabet.chars() {
+                if let Some(next_state) = self.get_next_state(state, letter) {
+                    if access_words[next_state].is_none() {
+                        access_words[next_state] = Some(format!("{word}{letter}"));
+                        states_to_visit.push_back(next_state);
+                    }
+                }
+            }
+        }
+
+        access_words
+    }
+
+    // Тот же ДКА, в котором недостающие переходы ведут в добавленный сток
+    pub fn complete(&self, alphabet: &str) -> Self {
+        let sink = self.size;
+        let mut automata = Self::new(self.size + 1);
+        automata.finite_states[..self.size].copy_from_slice(&self.finite_states);
+
+        for state in 0..=sink {
+            for letter in alphabet.chars() {
+                let next_state = if state == sink {
+                    sink
+                } else {
+                    self.get_next_state(state, letter).unwrap_or(sink)
+                };
+                automata.transitions[state][next_state].insert(letter.to_string());
+            }
+        }
+
+        automata
+    }
+
+    // Минимальный полный ДКА того же языка; состояния нумеруются в порядке
+    // обхода в ширину из начального
+    pub fn minimize(&self, alphabet: &str) -> Self {
+        let letters: Vec<char> = alphabet.chars().collect();
+
+        // Недостающие переходы ведут в добавленное тупиковое состояние
+        let sink = self.size;
+        let get_next_state = |state: usize, letter: char| {
+            if state == sink {
+                return sink;
+            }
+            self.get_next_state(state, letter).unwrap_or(sink)
+        };
+
+        let mut classes: Vec<usize> = (0..=sink)
+            .map(|state| (state != sink && self.finite_states[state]) as usize)
+            .collect();
+        let mut classes_count = classes.iter().collect::<HashSet<_>>().len();
+        loop {
+            let mut signature_to_class = HashMap::new();
+            let next_classes: Vec<usize> = (0..=sink)
+                .map(|state| {
+                    let signature = (
+                        classes[state],
+                        Vec::from_iter(
+                            letters
+                                .iter()
+                                .map(|letter| classes[get_next_state(state, *letter)]),
+                        ),
+                    );
+                    let count = signature_to_class.len();
+                    *signature_to_class.entry(signature).or_insert(count)
+                })
+                .collect();
+
+            classes = next_classes;
+            if signature_to_class.len() == classes_count {
+                break;
+            }
+            classes_count = signature_to_class.len();
+        }
+
+        let mut class_to_state = HashMap::from([(classes[START], START)]);
+        let mut representatives = vec![START];
+        let mut index = 0;
+        while index < representatives.len() {
+            for letter in &letters {
+                let next_state = get_next_state(representatives[index], *letter);
+                if let Entry::Vacant(entry) = class_to_state.entry(classes[next_state]) {
+                    entry.insert(representatives.len());
+                    representatives.push(next_state);
+                }
+            }
+            index += 1;
+        }
+
+        let mut automata = Self::new(representatives.len());
+        for (state, representative) in representatives.iter().enumerate() {
+            automata.finite_states[state] =
+                *representative != sink && self.finite_states[*representative];
+            for letter in &letters {
+                let next_state = get_next_state(*representative, *letter);
+                let target = class_to_state[&classes[next_state]];
+                automata.transitions[state][target].insert(letter.to_string());
+            }
+        }
+
+        automata
+    }
+
+    pub fn get_next_state(&self, state: usize, letter: char) -> Option<usize> {
+        let letter = letter.to_string();
+        self.transitions[state]
+            .iter()
+            .position(|labels| labels.contains(&letter))
+    }
+
     fn get_epsilon_closure(&self, subset: &BTreeSet<usize>) -> BTreeSet<usize> {
         let mut closure = BTreeSet::<usize>::new();
 
@@ -175,3 +454,56 @@ impl AutomataImpl {
         visited_states
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_dfa(regex: &str) -> AutomataImpl {
+        let automata = crate::regex::parse(regex).unwrap();
+        get_automata_impl(automata.determinize().as_ref()).clone()
+    }
+
+    #[test]
+    fn minimize_merges_equivalent_states() {
+        // Состояния 1 и 2 эквивалентны: оба заключительные и ведут в себя
+        let automata =
+            AutomataImpl::parse("final 1 2\n0 a 1\n0 b 2\n1 a 1\n1 b 2\n2 a 1\n2 b 2").unwrap();
+        let minimal = automata.minimize("ab");
+
+        assert_eq!(minimal.size, 2);
+        assert_eq!(minimal.finite_states, [false, true]);
+        assert_eq!(automata.find_distinguishing_word(&minimal, "ab"), None);
+    }
+
+    #[test]
+    fn minimize_completes_automata() {
+        let minimal = get_dfa("ab").minimize("ab");
+
+        // Начальное, после `a`, после `ab` и сток
+        assert_eq!(minimal.size, 4);
+        for state in 0..minimal.size {
+            for letter in "ab".chars() {
+                assert!(minimal.get_next_state(state, letter).is_some());
+            }
+        }
+        assert!(minimal.check_membership("ab"));
+        assert!(!minimal.check_membership("aba"));
+    }
+
+    #[test]
+    fn minimize_keeps_language() {
+        for (regex, size) in [("∅", 1), ("(a|b)*", 1), ("(a|b)*a", 2), ("(a|b)*a(a|b)", 4)] {
+            let dfa = get_dfa(regex);
+            let minimal = dfa.minimize("ab");
+
+            assert_eq!(minimal.size, size, "{regex}");
+            assert_eq!(
+                dfa.find_distinguishing_word(&minimal, "ab"),
+                None,
+                "{regex}"
+            );
+            assert_eq!(minimal.minimize("ab"), minimal, "{regex}");
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use std::fs;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+// Сохранённое состояние обучения NL*, по которому его можно продолжить
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub alphabet: String,
+    // Число завершённых раундов
+    pub rounds: usize,
+    // Состояния ученика и учителя в форматах, которые знают только они сами
+    pub learner: serde_json::Value,
+    pub equivalence_position: Option<serde_json::Value>,
+}
+
+pub struct CheckpointConfig {
+    pub path: String,
+    // Состояние сохраняется каждые interval раундов, при 0 -- только при остановке
+    pub interval: usize,
+    // Выставляется обработчиком Ctrl-C; обучение останавливается, как только
+    // текущий раунд завершится или прервётся
+    pub interrupted: Arc<AtomicBool>,
+}
+
+impl Checkpoint {
+    pub fn load(path: &str) -> Result<Self> {
+        let text = fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+
+        serde_json::from_str(&text).map_err(|error| Error::Checkpoint(format!("{path}: {error}")))
+    }
+
+    // Файл заменяется целиком, чтобы прерванная запись не испортила прошлую точку
+    pub fn save(&self, path: &str) -> Result<()> {
+        let temporary_path = format!("{path}.tmp");
+        let to_error = |source| Error::Io {
+            path: path.to_owned(),
+            source,
+        };
+
+        fs::write(&temporary_path, serde_json::to_string(self).unwrap()).map_err(to_error)?;
+        fs::rename(&temporary_path, path).map_err(to_error)
+    }
+}
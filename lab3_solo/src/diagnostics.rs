@@ -0,0 +1,290 @@
+use std::collections::{BTreeSet, HashMap};
+
+use serde::Serialize;
+
+use crate::nl::render::TableView;
+
+// Семейства длиннее не ищутся: для отчёта достаточно нескольких членов
+const MAX_BASE_LEN: usize = 2;
+const MIN_FAMILY_SIZE: usize = 3;
+const MAX_FAMILIES: usize = 3;
+
+// Признаки того, что целевой язык может быть нерегулярным. Это не
+// доказательство: оценки получены по конечному числу запросов.
+#[derive(Serialize)]
+pub struct Diagnostics {
+    // Число последних раундов подряд, в которых гипотеза росла
+    pub growing_rounds: usize,
+    // Средний прирост числа состояний за раунд
+    pub states_per_round: f64,
+    pub counterexample_lengths: Vec<usize>,
+    pub families: Vec<PrefixFamily>,
+    // Число попарно различимых префиксов -- нижняя оценка числа состояний
+    // минимального ДКА по теореме Майхилла -- Нероуда
+    pub lower_bound: usize,
+}
+
+// Степени слова base, попарно различимые суффиксами из таблицы
+#[derive(Serialize)]
+pub struct PrefixFamily {
+    pub base: String,
+    pub prefixes: Vec<String>,
+    // Суффикс, отличающий префикс от всех остальных членов семейства
+    pub witnesses: Vec<Option<String>>,
+}
+
+impl Diagnostics {
+    // Без таблицы нижней оценкой служит размер последней гипотезы: её состояния
+    // L*, KV и TTT различают запросами принадлежности
+    pub fn new(
+        hypothesis_sizes: &[usize],
+        counterexamples: &[String],
+        table: Option<&TableView>,
+        alphabet: &str,
+    ) -> Self {
+        let growing_rounds = count_trailing_growth(hypothesis_sizes);
+        let states_per_round = match hypothesis_sizes {
+            [first, .., last] => {
+                (*last as f64 - *first as f64) / (hypothesis_sizes.len() - 1) as f64
+            }
+            _ => 0.0,
+        };
+
+        let (families, lower_bound) = match table {
+            Some(table) => (
+                find_families(table, alphabet),
+                BTreeSet::from_iter(table.rows.iter().map(|row| &row.cells)).len(),
+            ),
+            None => (Vec::new(), hypothesis_sizes.last().copied().unwrap_or(0)),
+        };
+
+        Self {
+            growing_rounds,
+            states_per_round,
+            counterexample_lengths: counterexamples
+                .iter()
+                .map(|word| word.chars().count())
+                .collect(),
+            families,
+            lower_bound,
+        }
+    }
+
+    pub fn render_text(&self) -> String {
+        let mut lines = vec!["non-regularity evidence:".to_string()];
+        lines.push(format!(
+            "  hypothesis grew in each of the last {} rounds, by {:.1} states per round on average",
+            self.growing_rounds, self.states_per_round
+        ));
+
+        let lengths: Vec<String> = self
+            .counterexample_lengths
+            .iter()
+            .map(usize::to_string)
+            .collect();
+        lines.push(format!("  counterexample lengths: {}", lengths.join(", ")));
+
+        for family in &self.families {
+            let base = if family.base.chars().count() > 1 {
+                format!("({})", family.base)
+            } else {
+                family.base.to_owned()
+            };
+            let witnesses: Vec<String> = family
+                .witnesses
+                .iter()
+                .map(|witness| match witness.as_deref() {
+                    Some("") => "ε".to_string(),
+                    Some(witness) => witness.to_owned(),
+                    None => "-".to_string(),
+                })
+                .collect();
+            lines.push(format!(
+                "  pairwise distinguishable prefixes {base}^0..{base}^{}, witnesses: {}",
+                family.prefixes.len() - 1,
+                witnesses.join(", ")
+            ));
+        }
+
+        lines.push(format!(
+            "  Myhill-Nerode lower bound on the number of states: {}",
+            self.lower_bound
+        ));
+
+        lines.join("\n") + "\n"
+    }
+}
+
+// Число последних шагов подряд, на которых значение росло
+pub fn count_trailing_growth(values: &[usize]) -> usize {
+    values
+        .windows(2)
+        .rev()
+        .take_while(|sizes| sizes[1] > sizes[0])
+        .count()
+}
+
+// Для каждого основания u берётся самый длинный ряд u^0, u^1, ... из строк
+// таблицы, в котором все строки различны
+fn find_families(table: &TableView, alphabet: &str) -> Vec<PrefixFamily> {
+    let rows = HashMap::<&str, &Vec<bool>>::from_iter(
+        table
+            .rows
+            .iter()
+            .map(|row| (row.prefix.as_str(), &row.cells)),
+    );
+
+    let mut families: Vec<PrefixFamily> = get_bases(alphabet)
+        .into_iter()
+        .filter_map(|base| {
+            let mut prefixes = Vec::new();
+            let mut seen_rows = BTreeSet::new();
+            let mut prefix = String::new();
+            while let Some(row) = rows.get(prefix.as_str()) {
+                if !seen_rows.insert(*row) {
+                    break;
+                }
+                prefixes.push(prefix.to_owned());
+                prefix.push_str(&base);
+            }
+
+            (prefixes.len() >= MIN_FAMILY_SIZE).then(|| PrefixFamily {
+                witnesses: find_witnesses(table, &rows, &prefixes),
+                base,
+                prefixes,
+            })
+        })
+        .collect();
+
+    families.sort_by_key(|family| std::cmp::Reverse(family.prefixes.len()));
+    families.truncate(MAX_FAMILIES);
+    families
+}
+
+fn find_witnesses(
+    table: &TableView,
+    rows: &HashMap<&str, &Vec<bool>>,
+    prefixes: &[String],
+) -> Vec<Option<String>> {
+    let family_rows: Vec<&Vec<bool>> = prefixes
+        .iter()
+        .map(|prefix| rows[prefix.as_str()])
+        .collect();
+
+    family_rows
+        .iter()
+        .enumerate()
+        .map(|(index, row)| {
+            (0..table.suffixes.len())
+                .find(|suffix_id| {
+                    family_rows.iter().enumerate().all(|(other, other_row)| {
+                        other == index || other_row[*suffix_id] != row[*suffix_id]
+                    })
+                })
+                .map(|suffix_id| table.suffixes[suffix_id].to_owned())
+        })
+        .collect()
+}
+
+// Непустые слова длины не больше MAX_BASE_LEN, не являющиеся степенями более
+// коротких слов
+fn get_bases(alphabet: &str) -> Vec<String> {
+    let mut words = vec![String::new()];
+    let mut bases = Vec::new();
+    for _ in 0..MAX_BASE_LEN {
+        words = words
+            .iter()
+            .flat_map(|word| {
+                alphabet
+                    .chars()
+                    .map(move |letter| format!("{word}{letter}"))
+            })
+            .collect();
+        bases.extend(words.iter().filter(|word| is_primitive(word)).cloned());
+    }
+    bases
+}
+
+fn is_primitive(word: &str) -> bool {
+    let letters: Vec<char> = word.chars().collect();
+    (1..letters.len())
+        .filter(|period| letters.len().is_multiple_of(*period))
+        .all(|period| {
+            (period..letters.len()).any(|index| letters[index] != letters[index - period])
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nl::render::TableRow;
+
+    // Фрагмент таблицы для a^n b^n: строка ab совпадает со строкой ε
+    fn get_table() -> TableView {
+        let row = |prefix: &str, cells: [bool; 4]| TableRow {
+            prefix: prefix.to_string(),
+            is_upper: true,
+            is_prime: true,
+            cells: cells.to_vec(),
+        };
+
+        TableView {
+            suffixes: ["", "b", "bb", "bbb"].map(str::to_string).to_vec(),
+            rows: vec![
+                row("", [true, false, false, false]),
+                row("a", [false, true, false, false]),
+                row("aa", [false, false, true, false]),
+                row("aaa", [false, false, false, true]),
+                row("b", [false, false, false, false]),
+                row("ab", [true, false, false, false]),
+            ],
+        }
+    }
+
+    #[test]
+    fn counts_trailing_growth() {
+        assert_eq!(count_trailing_growth(&[]), 0);
+        assert_eq!(count_trailing_growth(&[3]), 0);
+        assert_eq!(count_trailing_growth(&[3, 1, 2, 4]), 2);
+        assert_eq!(count_trailing_growth(&[1, 2, 2]), 0);
+    }
+
+    #[test]
+    fn takes_primitive_words_as_bases() {
+        assert!(is_primitive("a"));
+        assert!(is_primitive("aab"));
+        assert!(!is_primitive("aa"));
+        assert!(!is_primitive("abab"));
+        assert_eq!(get_bases("ab"), ["a", "b", "ab", "ba"]);
+    }
+
+    #[test]
+    fn finds_distinguishable_powers() {
+        let table = get_table();
+        let diagnostics = Diagnostics::new(&[1, 2, 4, 7], &[], Some(&table), "ab");
+        assert_eq!(diagnostics.growing_rounds, 3);
+        assert_eq!(diagnostics.states_per_round, 2.0);
+        assert_eq!(diagnostics.lower_bound, 5);
+
+        // Ряды b и ab короче MIN_FAMILY_SIZE
+        assert_eq!(diagnostics.families.len(), 1);
+        let family = &diagnostics.families[0];
+        assert_eq!(family.base, "a");
+        assert_eq!(family.prefixes, ["", "a", "aa", "aaa"]);
+        assert_eq!(
+            family.witnesses,
+            [Some(""), Some("b"), Some("bb"), Some("bbb")].map(|witness| witness.map(String::from))
+        );
+        assert!(diagnostics
+            .render_text()
+            .contains("pairwise distinguishable prefixes a^0..a^3, witnesses: ε, b, bb, bbb\n"));
+    }
+
+    #[test]
+    fn bounds_states_by_hypothesis_without_table() {
+        let diagnostics = Diagnostics::new(&[2, 5], &["aab".to_string()], None, "ab");
+        assert!(diagnostics.families.is_empty());
+        assert_eq!(diagnostics.lower_bound, 5);
+        assert_eq!(diagnostics.counterexample_lengths, [3]);
+    }
+}
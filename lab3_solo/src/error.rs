@@ -0,0 +1,64 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::learner::Limit;
+
+#[derive(Debug)]
+pub enum Error {
+    Spawn { word: String, source: std::io::Error },
+    Timeout { word: String, timeout: Duration },
+    ExitCode { word: String, code: Option<i32> },
+    InvalidOutput { word: String, output: String },
+    Io { path: String, source: std::io::Error },
+    Parse(String),
+    UnseenQuery(String),
+    Checkpoint(String),
+    Seed(String),
+    // Обучение остановлено по Ctrl-C, состояние сохранено в контрольной точке
+    Interrupted { path: String },
+    // Исчерпано ограничение на обучение; learn превращает эту ошибку в статус
+    LimitReached(Limit),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Spawn { word, source } => {
+                write!(f, "failed to run oracle on word {word:?}: {source}")
+            }
+            Error::Timeout { word, timeout } => {
+                write!(f, "oracle timed out after {timeout:?} on word {word:?}")
+            }
+            Error::ExitCode {
+                word,
+                code: Some(code),
+            } => write!(f, "oracle exited with code {code} on word {word:?}"),
+            Error::ExitCode { word, code: None } => {
+                write!(f, "oracle was terminated by a signal on word {word:?}")
+            }
+            Error::InvalidOutput { word, output } => {
+                write!(f, "oracle printed {output:?} on word {word:?}")
+            }
+            Error::Io { path, source } => write!(f, "{path}: {source}"),
+            Error::Parse(message) => write!(f, "{message}"),
+            Error::UnseenQuery(query) => write!(f, "query is missing from the transcript: {query}"),
+            Error::Checkpoint(message) => write!(f, "checkpoint: {message}"),
+            Error::Seed(message) => write!(f, "seed: {message}"),
+            Error::Interrupted { path } => {
+                write!(f, "learning was interrupted, its state is saved to {path}")
+            }
+            Error::LimitReached(limit) => write!(f, "{limit} is reached"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Spawn { source, .. } | Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::automata::AutomataImpl;
+use crate::nl::render::{TableFormat, TableView};
+
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub enum Verbosity {
+    Quiet,
+    // Исправления таблицы и контрпримеры
+    Info,
+    // Вдобавок таблица, переходы и гипотеза каждого раунда
+    Debug,
+}
+
+#[derive(Clone, Copy)]
+pub enum EventFormat {
+    Text,
+    // По объекту JSON на строку
+    Json,
+}
+
+#[derive(Clone, Copy, Serialize)]
+pub struct TableSize {
+    pub upper: usize,
+    pub lower: usize,
+    pub suffixes: usize,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    FixCompleteness {
+        round: usize,
+        prefix: &'a str,
+        table_size: TableSize,
+    },
+    FixConsistency {
+        round: usize,
+        suffix: &'a str,
+        table_size: TableSize,
+    },
+    Counterexample {
+        round: usize,
+        word: &'a str,
+        table_size: TableSize,
+    },
+    Table {
+        round: usize,
+        table: &'a TableView,
+    },
+    AddTransition {
+        round: usize,
+        from: usize,
+        to: usize,
+        letter: char,
+    },
+    Hypothesis {
+        round: usize,
+        automata: &'a AutomataImpl,
+    },
+}
+
+impl Event<'_> {
+    fn get_verbosity(&self) -> Verbosity {
+        match self {
+            Self::FixCompleteness { .. }
+            | Self::FixConsistency { .. }
+            | Self::Counterexample { .. } => Verbosity::Info,
+            Self::Table { .. } | Self::AddTransition { .. } | Self::Hypothesis { .. } => {
+                Verbosity::Debug
+            }
+        }
+    }
+}
+
+// Печатает события обучения в stderr, чтобы stdout содержал только результат
+#[derive(Clone, Copy)]
+pub struct EventLog {
+    verbosity: Verbosity,
+    format: EventFormat,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new(Verbosity::Quiet, EventFormat::Text)
+    }
+}
+
+impl EventLog {
+    pub fn new(verbosity: Verbosity, format: EventFormat) -> Self {
+        Self { verbosity, format }
+    }
+
+    pub fn is_enabled(&self, verbosity: Verbosity) -> bool {
+        verbosity <= self.verbosity
+    }
+
+    pub fn emit(&self, event: &Event) {
+        if !self.is_enabled(event.get_verbosity()) {
+            return;
+        }
+
+        let text = match self.format {
+            EventFormat::Text => format_text(event),
+            EventFormat::Json => serde_json::to_string(event).unwrap() + "\n",
+        };
+        // Журнал не должен прерывать обучение, поэтому ошибки записи игнорируются
+        let _ = io::stderr().lock().write_all(text.as_bytes());
+    }
+}
+
+fn format_text(event: &Event) -> String {
+    let show_size = |size: &TableSize| {
+        format!(
+            "|S| = {}, |S·Σ| = {}, |E| = {}",
+            size.upper, size.lower, size.suffixes
+        )
+    };
+
+    match event {
+        Event::FixCompleteness {
+            round,
+            prefix,
+            table_size,
+        } => format!(
+            "[round {round}] fix completeness: prefix {prefix:?} ({})\n",
+            show_size(table_size)
+        ),
+        Event::FixConsistency {
+            round,
+            suffix,
+            table_size,
+        } => format!(
+            "[round {round}] fix consistency: suffix {suffix:?} ({})\n",
+            show_size(table_size)
+        ),
+        Event::Counterexample {
+            round,
+            word,
+            table_size,
+        } => format!(
+            "[round {round}] counterexample {word:?} ({})\n",
+            show_size(table_size)
+        ),
+        Event::Table { round, table } => {
+            format!("[round {round}] table:\n{}", table.render(TableFormat::Text))
+        }
+        Event::AddTransition {
+            round,
+            from,
+            to,
+            letter,
+        } => format!("[round {round}] add transition {from} to {to} by {letter}\n"),
+        Event::Hypothesis { round, automata } => {
+            format!("[round {round}] hypothesis: {automata:?}\n")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE_SIZE: TableSize = TableSize {
+        upper: 1,
+        lower: 2,
+        suffixes: 3,
+    };
+
+    #[test]
+    fn filters_events_by_verbosity() {
+        let counterexample = Event::Counterexample {
+            round: 1,
+            word: "ab",
+            table_size: TABLE_SIZE,
+        };
+        let transition = Event::AddTransition {
+            round: 1,
+            from: 0,
+            to: 1,
+            letter: 'a',
+        };
+
+        let is_shown = |verbosity, event: &Event| {
+            EventLog::new(verbosity, EventFormat::Text).is_enabled(event.get_verbosity())
+        };
+        assert!(!is_shown(Verbosity::Quiet, &counterexample));
+        assert!(is_shown(Verbosity::Info, &counterexample));
+        assert!(!is_shown(Verbosity::Info, &transition));
+        assert!(is_shown(Verbosity::Debug, &transition));
+        assert!(!EventLog::default().is_enabled(Verbosity::Info));
+    }
+
+    #[test]
+    fn formats_events_as_text() {
+        assert_eq!(
+            format_text(&Event::FixCompleteness {
+                round: 2,
+                prefix: "ab",
+                table_size: TABLE_SIZE,
+            }),
+            "[round 2] fix completeness: prefix \"ab\" (|S| = 1, |S·Σ| = 2, |E| = 3)\n"
+        );
+        assert_eq!(
+            format_text(&Event::AddTransition {
+                round: 3,
+                from: 0,
+                to: 1,
+                letter: 'b',
+            }),
+            "[round 3] add transition 0 to 1 by b\n"
+        );
+    }
+
+    #[test]
+    fn formats_events_as_json() {
+        let event = Event::FixConsistency {
+            round: 4,
+            suffix: "ba",
+            table_size: TABLE_SIZE,
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({
+                "event": "fix_consistency",
+                "round": 4,
+                "suffix": "ba",
+                "table_size": {"upper": 1, "lower": 2, "suffixes": 3},
+            })
+        );
+    }
+}
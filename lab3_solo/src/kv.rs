@@ -0,0 +1,171 @@
+mod discrimination_tree;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::automata::{AutomataImpl, EPSILON, START};
+use crate::error::Result;
+use crate::kv::discrimination_tree::{DiscriminationTree, Node, ROOT};
+use crate::learner::{Learner, RoundResult};
+use crate::mat::{EquivalenceCheckResult, Mat};
+
+// Алгоритм Кернса–Вазирани: состояния гипотезы -- листья дерева различения,
+// переходы находятся просеиванием слов через дерево
+pub struct KvImpl<'a> {
+    mat: &'a dyn Mat,
+    tree: Option<DiscriminationTree>,
+    // Ответы запоминаются, так как просеивание повторяется при каждой перестройке
+    memberships: RefCell<HashMap<String, bool>>,
+}
+
+impl<'a> Learner for KvImpl<'a> {
+    fn run_round(&mut self) -> Result<RoundResult> {
+        let (dfa, state_words) = self.build_dfa()?;
+
+        let EquivalenceCheckResult::Counterexample(word) = self.mat.check_equivalence(&dfa)? else {
+            return Ok(RoundResult::Finished(Box::new(dfa)));
+        };
+
+        match &self.tree {
+            None => {
+                // Гипотеза из одного состояния ошибается на контрпримере,
+                // поэтому он отличается от ε пустым суффиксом
+                let tree = if self.check_membership(EPSILON)? {
+                    DiscriminationTree::new(EPSILON, &word)
+                } else {
+                    DiscriminationTree::new(&word, EPSILON)
+                };
+                self.tree = Some(tree);
+            }
+            Some(_) => self.process_counterexample(&word, &dfa, &state_words)?,
+        }
+
+        Ok(RoundResult::Counterexample(word))
+    }
+}
+
+impl<'a> KvImpl<'a> {
+    pub fn new(mat: &'a dyn Mat) -> Self {
+        Self {
+            mat,
+            tree: None,
+            memberships: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn check_membership(&self, word: &str) -> Result<bool> {
+        if let Some(answer) = self.memberships.borrow().get(word) {
+            return Ok(*answer);
+        }
+
+        let answer = self.mat.check_membership(word)?;
+        self.memberships.borrow_mut().insert(word.to_owned(), answer);
+        Ok(answer)
+    }
+
+    fn sift(&self, word: &str) -> Result<usize> {
+        let tree = self.tree.as_ref().unwrap();
+        let mut node = ROOT;
+
+        while let Node::Inner {
+            discriminator,
+            children,
+        } = &tree.nodes[node]
+        {
+            let is_member = self.check_membership(&format!("{word}{discriminator}"))?;
+            node = children[is_member as usize];
+        }
+
+        Ok(node)
+    }
+
+    // Возвращает гипотезу и слова доступа к её состояниям
+    fn build_dfa(&self) -> Result<(AutomataImpl, Vec<String>)> {
+        let alphabet = self.mat.get_alphabet();
+
+        let Some(tree) = &self.tree else {
+            let mut automata = AutomataImpl::new(1);
+            for letter in alphabet.chars() {
+                automata.transitions[START][START].insert(letter.to_string());
+            }
+            automata.finite_states[START] = self.check_membership(EPSILON)?;
+            return Ok((automata, vec![EPSILON.to_owned()]));
+        };
+
+        // Лист слова ε становится стартовым состоянием
+        let mut leaves = tree.get_leaves();
+        let start_leaf = tree.find_leaf(EPSILON);
+        leaves.retain(|leaf| *leaf != start_leaf);
+        leaves.insert(START, start_leaf);
+
+        let leaf_to_state: HashMap<usize, usize> = leaves
+            .iter()
+            .enumerate()
+            .map(|(state, leaf)| (*leaf, state))
+            .collect();
+
+        let mut automata = AutomataImpl::new(leaves.len());
+        for (state, leaf) in leaves.iter().enumerate() {
+            let access_word = tree.get_access_word(*leaf);
+            for letter in alphabet.chars() {
+                let next_leaf = self.sift(&format!("{access_word}{letter}"))?;
+                let next_state = leaf_to_state.get(&next_leaf).unwrap();
+                automata.transitions[state][*next_state].insert(letter.to_string());
+            }
+            automata.finite_states[state] = self.check_membership(access_word)?;
+        }
+
+        let state_words = leaves
+            .iter()
+            .map(|leaf| tree.get_access_word(*leaf).to_owned())
+            .collect();
+
+        Ok((automata, state_words))
+    }
+
+    // Находит первый префикс контрпримера, на котором гипотеза расходится с
+    // просеиванием, и расщепляет состояние предыдущего префикса
+    fn process_counterexample(
+        &mut self,
+        counterexample: &str,
+        dfa: &AutomataImpl,
+        state_words: &[String],
+    ) -> Result<()> {
+        let tree = self.tree.as_ref().unwrap();
+        let mut previous_prefix = EPSILON;
+
+        for (index, letter) in counterexample.char_indices() {
+            let end = index + letter.len_utf8();
+            let prefix = &counterexample[..end];
+
+            let hypothesis_word = &state_words[dfa.get_state(prefix).unwrap()];
+            let hypothesis_leaf = tree.find_leaf(hypothesis_word);
+            let sifted_leaf = self.sift(prefix)?;
+
+            if hypothesis_leaf != sifted_leaf {
+                let separator = tree.get_separator(hypothesis_leaf, sifted_leaf);
+                let discriminator = format!("{letter}{separator}");
+
+                let split_word = &state_words[dfa.get_state(previous_prefix).unwrap()];
+                let split_leaf = tree.find_leaf(split_word);
+                let is_new_accepted =
+                    self.check_membership(&format!("{previous_prefix}{discriminator}"))?;
+
+                self.tree.as_mut().unwrap().split_leaf(
+                    split_leaf,
+                    &discriminator,
+                    previous_prefix,
+                    is_new_accepted,
+                );
+                return Ok(());
+            }
+
+            previous_prefix = prefix;
+        }
+
+        // Сюда можно попасть, только если учитель вернул слово, на котором
+        // гипотеза не ошибается; тогда следующий запрос эквивалентности
+        // вернёт другой контрпример
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+use crate::automata::EPSILON;
+
+pub enum Node {
+    Leaf {
+        access_word: String,
+    },
+    // Дети упорядочены по ответу на запрос принадлежности: [нет, да]
+    Inner {
+        discriminator: String,
+        children: [usize; 2],
+    },
+}
+
+pub struct DiscriminationTree {
+    pub nodes: Vec<Node>,
+    parents: Vec<Option<usize>>,
+}
+
+pub const ROOT: usize = 0;
+
+impl DiscriminationTree {
+    // Дерево с корнем ε и двумя листьями, различимыми пустым суффиксом
+    pub fn new(accepted_word: &str, rejected_word: &str) -> Self {
+        let mut tree = Self {
+            nodes: Vec::new(),
+            parents: Vec::new(),
+        };
+
+        tree.add_node(
+            Node::Inner {
+                discriminator: EPSILON.to_owned(),
+                children: [1, 2],
+            },
+            None,
+        );
+        tree.add_node(
+            Node::Leaf {
+                access_word: rejected_word.to_owned(),
+            },
+            Some(ROOT),
+        );
+        tree.add_node(
+            Node::Leaf {
+                access_word: accepted_word.to_owned(),
+            },
+            Some(ROOT),
+        );
+
+        tree
+    }
+
+    fn add_node(&mut self, node: Node, parent: Option<usize>) -> usize {
+        self.nodes.push(node);
+        self.parents.push(parent);
+        self.nodes.len() - 1
+    }
+
+    pub fn get_leaves(&self) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|node| matches!(self.nodes[*node], Node::Leaf { .. }))
+            .collect()
+    }
+
+    pub fn get_access_word(&self, leaf: usize) -> &str {
+        match &self.nodes[leaf] {
+            Node::Leaf { access_word } => access_word,
+            Node::Inner { .. } => panic!("node {leaf} is not a leaf"),
+        }
+    }
+
+    pub fn find_leaf(&self, access_word: &str) -> usize {
+        self.get_leaves()
+            .into_iter()
+            .find(|leaf| self.get_access_word(*leaf) == access_word)
+            .unwrap()
+    }
+
+    // Различитель наименьшего общего предка двух узлов
+    pub fn get_separator(&self, first: usize, second: usize) -> &str {
+        let first_ancestors = self.get_ancestors(first);
+        let ancestor = self
+            .get_ancestors(second)
+            .into_iter()
+            .find(|node| first_ancestors.contains(node))
+            .unwrap();
+
+        match &self.nodes[ancestor] {
+            Node::Inner { discriminator, .. } => discriminator,
+            Node::Leaf { .. } => panic!("nodes {first} and {second} are equal"),
+        }
+    }
+
+    fn get_ancestors(&self, node: usize) -> Vec<usize> {
+        let mut ancestors = Vec::new();
+        let mut parent = self.parents[node];
+        while let Some(node) = parent {
+            ancestors.push(node);
+            parent = self.parents[node];
+        }
+        ancestors
+    }
+
+    // Лист превращается во внутренний узел с двумя новыми листьями
+    pub fn split_leaf(
+        &mut self,
+        leaf: usize,
+        discriminator: &str,
+        new_access_word: &str,
+        is_new_accepted: bool,
+    ) {
+        let old_access_word = self.get_access_word(leaf).to_owned();
+
+        let old_leaf = self.add_node(
+            Node::Leaf {
+                access_word: old_access_word,
+            },
+            Some(leaf),
+        );
+        let new_leaf = self.add_node(
+            Node::Leaf {
+                access_word: new_access_word.to_owned(),
+            },
+            Some(leaf),
+        );
+
+        let children = if is_new_accepted {
+            [old_leaf, new_leaf]
+        } else {
+            [new_leaf, old_leaf]
+        };
+        self.nodes[leaf] = Node::Inner {
+            discriminator: discriminator.to_owned(),
+            children,
+        };
+    }
+}
@@ -0,0 +1,508 @@
+use std::fmt;
+use std::fs;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::automata::{get_automata_impl, Automata, AutomataImpl};
+use crate::checkpoint::{Checkpoint, CheckpointConfig};
+use crate::diagnostics::{count_trailing_growth, Diagnostics};
+use crate::error::{Error, Result};
+use crate::events::{EventLog, TableSize};
+use crate::kv::KvImpl;
+use crate::lstar::LStarImpl;
+use crate::mat::counting::CountingMat;
+use crate::mat::Mat;
+use crate::nl::counterexample::CounterexampleStrategy;
+use crate::nl::render::TableView;
+use crate::nl::seed::Seed;
+use crate::nl::{NlImpl, Rfsa};
+use crate::ttt::TttImpl;
+
+pub enum RoundResult {
+    // Гипотеза опровергнута, контрпример уже учтён учеником
+    Counterexample(String),
+    Finished(Box<dyn Automata>),
+}
+
+// Раунд обучения -- достраивание гипотезы и один запрос эквивалентности
+pub trait Learner {
+    fn run_round(&mut self) -> Result<RoundResult>;
+
+    // Доступ к таблице наблюдений, если алгоритм её ведёт
+    fn as_table_learner(&self) -> Option<&dyn TableLearner> {
+        None
+    }
+
+    // Состояние для контрольной точки, если алгоритм умеет его сохранять; его
+    // формат знает только сам алгоритм
+    fn get_state(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    fn get_dfa(&mut self) -> Result<Box<dyn Automata>> {
+        loop {
+            if let RoundResult::Finished(dfa) = self.run_round()? {
+                break Ok(dfa);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Algorithm {
+    Nl,
+    LStar,
+    Kv,
+    Ttt,
+}
+
+pub struct LearnerConfig {
+    pub algorithm: Algorithm,
+    // Параметры обработки контрпримеров используются только в NL*
+    pub counterexample_strategy: CounterexampleStrategy,
+    pub shorten_counterexamples: bool,
+    pub event_log: EventLog,
+    pub checkpoint: Option<CheckpointConfig>,
+    // Начальное содержимое таблицы, только для NL*
+    pub seed: Seed,
+    pub limits: Limits,
+}
+
+impl Default for LearnerConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: Algorithm::Nl,
+            counterexample_strategy: CounterexampleStrategy::AllSuffixes,
+            shorten_counterexamples: false,
+            event_log: EventLog::default(),
+            checkpoint: None,
+            seed: Seed::default(),
+            limits: Limits::default(),
+        }
+    }
+}
+
+impl LearnerConfig {
+    pub fn build<'a>(&self, mat: &'a dyn Mat) -> Result<Box<dyn Learner + 'a>> {
+        if !matches!(self.algorithm, Algorithm::Nl) && !self.seed.is_empty() {
+            return Err(Error::Seed("only NL* can start from a seed".to_string()));
+        }
+
+        self.seed.verify(mat)?;
+
+        Ok(match self.algorithm {
+            Algorithm::Nl => Box::new(NlImpl::new(
+                mat,
+                self.counterexample_strategy,
+                self.shorten_counterexamples,
+                self.event_log,
+                &self.seed,
+            )?),
+            Algorithm::LStar => Box::new(LStarImpl::new(mat)?),
+            Algorithm::Kv => Box::new(KvImpl::new(mat)),
+            Algorithm::Ttt => Box::new(TttImpl::new(mat)?),
+        })
+    }
+
+    pub fn restore<'a>(
+        &self,
+        mat: &'a dyn Mat,
+        state: serde_json::Value,
+        rounds: usize,
+    ) -> Result<Box<dyn Learner + 'a>> {
+        match self.algorithm {
+            Algorithm::Nl => {
+                self.seed.verify(mat)?;
+                Ok(Box::new(NlImpl::from_state(
+                    mat,
+                    self.counterexample_strategy,
+                    self.shorten_counterexamples,
+                    self.event_log,
+                    state,
+                    rounds,
+                    &self.seed,
+                )?))
+            }
+            _ => Err(Error::Checkpoint(
+                "only NL* can be resumed from a checkpoint".to_string(),
+            )),
+        }
+    }
+}
+
+// Ограничения на обучение; None -- без ограничения
+#[derive(Clone, Copy, Default)]
+pub struct Limits {
+    pub membership_queries: Option<usize>,
+    pub rounds: Option<usize>,
+    // Гипотеза с большим числом состояний не предъявляется учителю
+    pub states: Option<usize>,
+    pub time: Option<Duration>,
+    // Число раундов подряд, в которых гипотеза росла, а контрпримеры удлинялись;
+    // так обучение идёт на нерегулярном языке, где оно не закончится
+    pub growing_rounds: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Limit {
+    MembershipQueries,
+    Rounds,
+    States,
+    Time,
+    Growth,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::MembershipQueries => write!(f, "membership query limit"),
+            Limit::Rounds => write!(f, "round limit"),
+            Limit::States => write!(f, "hypothesis state limit"),
+            Limit::Time => write!(f, "time limit"),
+            Limit::Growth => write!(f, "hypothesis growth limit"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    // Учитель принял гипотезу
+    Finished,
+    // Обучение остановлено, результат -- последняя построенная гипотеза
+    LimitReached(Limit),
+}
+
+// Расширение для алгоритмов с таблицей наблюдений (NL* и L*); саму таблицу и
+// остаточный автомат для отчёта строит только NL*
+pub trait TableLearner {
+    fn get_table_statistics(&self) -> TableStatistics;
+
+    fn get_table_view(&self) -> Option<TableView> {
+        None
+    }
+
+    fn get_rfsa(&self) -> Option<Rfsa> {
+        None
+    }
+}
+
+#[derive(Clone, Copy, Serialize)]
+pub struct TableStatistics {
+    pub closedness_repairs: usize,
+    pub consistency_repairs: usize,
+    pub size: TableSize,
+}
+
+#[derive(Serialize)]
+pub struct Statistics {
+    pub status: Status,
+    pub states: usize,
+    pub rounds: usize,
+    pub membership_queries: usize,
+    pub unique_membership_queries: usize,
+    // Часть запросов принадлежности, сделанная тестами эквивалентности
+    pub test_membership_queries: usize,
+    pub equivalence_queries: usize,
+    // Для алгоритмов без таблицы наблюдений отсутствует
+    pub table: Option<TableStatistics>,
+    // Число состояний гипотезы, предъявленной в каждом раунде
+    pub hypothesis_sizes: Vec<usize>,
+    // Время меняется от запуска к запуску, поэтому в отчёт попадает только по запросу
+    #[serde(skip)]
+    pub oracle_time: Duration,
+    #[serde(skip)]
+    pub learner_time: Duration,
+}
+
+// Статистика вместе со временем в секундах
+#[derive(Serialize)]
+struct TimedStatistics<'a> {
+    #[serde(flatten)]
+    statistics: &'a Statistics,
+    oracle_time: f64,
+    learner_time: f64,
+}
+
+impl Statistics {
+    pub fn render_text(&self, show_time: bool) -> String {
+        let status = match self.status {
+            Status::Finished => "finished".to_string(),
+            Status::LimitReached(limit) => format!("stopped by the {limit}"),
+        };
+        let mut lines = vec![
+            format!("status: {status}"),
+            format!("states: {}", self.states),
+            format!("rounds: {}", self.rounds),
+            format!(
+                "membership queries: {} (unique: {}, by equivalence tests: {})",
+                self.membership_queries,
+                self.unique_membership_queries,
+                self.test_membership_queries
+            ),
+            format!("equivalence queries: {}", self.equivalence_queries),
+        ];
+
+        if let Some(table) = &self.table {
+            lines.push(format!(
+                "repairs: closedness {}, consistency {}",
+                table.closedness_repairs, table.consistency_repairs
+            ));
+            lines.push(format!(
+                "table: |S| = {}, |S·Σ| = {}, |E| = {}",
+                table.size.upper, table.size.lower, table.size.suffixes
+            ));
+        }
+
+        let sizes: Vec<String> = self.hypothesis_sizes.iter().map(usize::to_string).collect();
+        lines.push(format!("hypothesis sizes: {}", sizes.join(", ")));
+        if show_time {
+            lines.push(format!(
+                "time: oracle {:.3} s, learner {:.3} s",
+                self.oracle_time.as_secs_f64(),
+                self.learner_time.as_secs_f64()
+            ));
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    pub fn render_json(&self, show_time: bool) -> String {
+        if !show_time {
+            return serde_json::to_string(self).unwrap();
+        }
+        serde_json::to_string(&TimedStatistics {
+            statistics: self,
+            oracle_time: self.oracle_time.as_secs_f64(),
+            learner_time: self.learner_time.as_secs_f64(),
+        })
+        .unwrap()
+    }
+}
+
+// Сохраняет состояние по расписанию, после ошибки и при остановке по Ctrl-C
+// или по ограничению
+fn save_checkpoint(
+    config: &CheckpointConfig,
+    round_result: &Result<RoundResult>,
+    is_stopping: bool,
+    learner: &dyn Learner,
+    mat: &dyn Mat,
+    rounds: usize,
+) -> Result<()> {
+    let is_interrupted = config.interrupted.load(Ordering::SeqCst);
+    let is_due = match round_result {
+        Ok(RoundResult::Finished(_)) => return Ok(()),
+        Ok(RoundResult::Counterexample(_)) => {
+            is_interrupted
+                || is_stopping
+                || (config.interval > 0 && rounds.is_multiple_of(config.interval))
+        }
+        // Строки таблицы добавляются только целиком, поэтому её можно сохранить и
+        // после ошибки, в том числе оракула, убитого тем же Ctrl-C
+        Err(_) => true,
+    };
+
+    if is_due {
+        Checkpoint {
+            alphabet: mat.get_alphabet(),
+            rounds,
+            learner: learner.get_state().unwrap(),
+            equivalence_position: mat.get_equivalence_position(),
+        }
+        .save(&config.path)?;
+    }
+
+    if is_interrupted {
+        return Err(Error::Interrupted {
+            path: config.path.to_owned(),
+        });
+    }
+
+    Ok(())
+}
+
+// KV и TTT и на регулярном языке растят гипотезу каждый раунд, поэтому
+// учитывается и длина контрпримеров
+fn is_growing(counting_mat: &CountingMat, rounds: usize) -> bool {
+    let counterexample_lengths: Vec<usize> = counting_mat
+        .get_counterexamples()
+        .iter()
+        .map(|word| word.chars().count())
+        .collect();
+
+    count_trailing_growth(&counting_mat.get_hypothesis_sizes()) >= rounds
+        && count_trailing_growth(&counterexample_lengths) >= rounds
+}
+
+pub struct LearningResult {
+    pub hypothesis: AutomataImpl,
+    pub rfsa: Option<Rfsa>,
+    pub table: Option<TableView>,
+    pub statistics: Statistics,
+    // Признаки нерегулярности языка, если обучение остановлено ограничением
+    pub diagnostics: Option<Diagnostics>,
+}
+
+pub fn learn(config: &LearnerConfig, mat: &dyn Mat) -> Result<LearningResult> {
+    run(config, mat, None)
+}
+
+// Продолжает обучение с контрольной точки; раунды считаются с начала обучения,
+// остальная статистика -- только за текущий запуск
+pub fn resume(
+    config: &LearnerConfig,
+    mat: &dyn Mat,
+    checkpoint: Checkpoint,
+) -> Result<LearningResult> {
+    if checkpoint.alphabet != mat.get_alphabet() {
+        return Err(Error::Checkpoint(format!(
+            "it was saved for alphabet {:?}",
+            checkpoint.alphabet
+        )));
+    }
+
+    run(config, mat, Some(checkpoint))
+}
+
+// Проводит раунды до конца обучения или до ограничения; возвращает статус,
+// последнюю гипотезу и число раундов с начала обучения
+fn run_rounds(
+    config: &LearnerConfig,
+    learner: &mut dyn Learner,
+    counting_mat: &CountingMat,
+    initial_rounds: usize,
+) -> Result<(Status, Option<AutomataImpl>, usize)> {
+    loop {
+        let round_result = learner.run_round();
+        // Раунд засчитывается, как только учитель ответил на запрос эквивалентности,
+        // даже если обработку контрпримера прервало ограничение
+        let rounds = initial_rounds + counting_mat.get_answered_equivalence_queries();
+        let limits = &config.limits;
+        let reached_limit = match round_result {
+            Ok(RoundResult::Counterexample(_))
+                if limits.rounds.is_some_and(|max_rounds| rounds >= max_rounds) =>
+            {
+                Some(Limit::Rounds)
+            }
+            Ok(RoundResult::Counterexample(_))
+                if limits
+                    .growing_rounds
+                    .is_some_and(|max_rounds| is_growing(counting_mat, max_rounds)) =>
+            {
+                Some(Limit::Growth)
+            }
+            _ => None,
+        };
+        if let Some(checkpoint_config) = &config.checkpoint {
+            save_checkpoint(
+                checkpoint_config,
+                &round_result,
+                reached_limit.is_some(),
+                learner,
+                counting_mat,
+                rounds,
+            )?;
+        }
+
+        match round_result {
+            Ok(RoundResult::Finished(dfa)) => {
+                let dfa = get_automata_impl(dfa.as_ref()).clone();
+                return Ok((Status::Finished, Some(dfa), rounds));
+            }
+            Ok(RoundResult::Counterexample(_)) => {
+                if let Some(limit) = reached_limit {
+                    let status = Status::LimitReached(limit);
+                    return Ok((status, counting_mat.get_last_hypothesis(), rounds));
+                }
+            }
+            Err(Error::LimitReached(limit)) => {
+                let status = Status::LimitReached(limit);
+                return Ok((status, counting_mat.get_last_hypothesis(), rounds));
+            }
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+fn run(
+    config: &LearnerConfig,
+    mat: &dyn Mat,
+    checkpoint: Option<Checkpoint>,
+) -> Result<LearningResult> {
+    let start = Instant::now();
+    let counting_mat = CountingMat::new(mat, config.limits);
+    let initial_rounds = checkpoint.as_ref().map_or(0, |checkpoint| checkpoint.rounds);
+    let learner = match checkpoint {
+        Some(checkpoint) => {
+            if let Some(position) = checkpoint.equivalence_position {
+                counting_mat.set_equivalence_position(position)?;
+            }
+            config.restore(&counting_mat, checkpoint.learner, checkpoint.rounds)
+        }
+        None => config.build(&counting_mat),
+    };
+    let (learner, status, hypothesis, rounds) = match learner {
+        Ok(mut learner) => {
+            if config.checkpoint.is_some() && learner.get_state().is_none() {
+                return Err(Error::Checkpoint(
+                    "only NL* can save checkpoints".to_string(),
+                ));
+            }
+            let (status, hypothesis, rounds) =
+                run_rounds(config, learner.as_mut(), &counting_mat, initial_rounds)?;
+            (Some(learner), status, hypothesis, rounds)
+        }
+        // Ограничение может исчерпаться ещё при заполнении начальной таблицы
+        Err(Error::LimitReached(limit)) => {
+            (None, Status::LimitReached(limit), None, initial_rounds)
+        }
+        Err(error) => return Err(error),
+    };
+
+    // Законченное обучение продолжать незачем; остановленное по ограничению
+    // можно продолжить с контрольной точки, ослабив ограничение
+    if let (Status::Finished, Some(checkpoint_config)) = (status, &config.checkpoint) {
+        let _ = fs::remove_file(&checkpoint_config.path);
+    }
+
+    // До первой гипотезы ученик ничего не предъявлял, и результатом считается
+    // автомат пустого языка
+    let hypothesis = hypothesis.unwrap_or_else(|| AutomataImpl::new(1));
+    let oracle_time = counting_mat.get_oracle_time();
+    let table_learner = learner.as_ref().and_then(|learner| learner.as_table_learner());
+    let table = table_learner.and_then(TableLearner::get_table_view);
+    let hypothesis_sizes = counting_mat.get_hypothesis_sizes();
+    let diagnostics = match status {
+        Status::Finished => None,
+        Status::LimitReached(_) => Some(Diagnostics::new(
+            &hypothesis_sizes,
+            &counting_mat.get_counterexamples(),
+            table.as_ref(),
+            &mat.get_alphabet(),
+        )),
+    };
+
+    Ok(LearningResult {
+        rfsa: table_learner.and_then(TableLearner::get_rfsa),
+        table,
+        statistics: Statistics {
+            status,
+            states: hypothesis.size,
+            rounds,
+            membership_queries: counting_mat.get_membership_queries(),
+            unique_membership_queries: counting_mat.get_unique_membership_queries(),
+            test_membership_queries: counting_mat.get_test_membership_queries(),
+            equivalence_queries: counting_mat.get_equivalence_queries(),
+            table: table_learner.map(TableLearner::get_table_statistics),
+            hypothesis_sizes,
+            oracle_time,
+            learner_time: start.elapsed().saturating_sub(oracle_time),
+        },
+        diagnostics,
+        hypothesis,
+    })
+}
@@ -0,0 +1,12 @@
+pub mod automata;
+pub mod checkpoint;
+pub mod diagnostics;
+pub mod error;
+pub mod events;
+pub mod kv;
+pub mod learner;
+pub mod lstar;
+pub mod mat;
+pub mod nl;
+pub mod regex;
+pub mod ttt;
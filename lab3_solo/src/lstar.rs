@@ -0,0 +1,163 @@
+mod observation_table;
+
+use std::collections::HashMap;
+
+use crate::automata::{AutomataImpl, START};
+use crate::error::Result;
+use crate::lstar::observation_table::ObservationTable;
+use crate::events::TableSize;
+use crate::learner::{Learner, RoundResult, TableLearner, TableStatistics};
+use crate::mat::{EquivalenceCheckResult, Mat};
+
+// Классический алгоритм L* Англюин: строит минимальный ДКА по одной таблице
+// наблюдений, проверяя её замкнутость и согласованность
+pub struct LStarImpl<'a> {
+    mat: &'a dyn Mat,
+    table: ObservationTable<'a>,
+    closedness_repairs: usize,
+    consistency_repairs: usize,
+}
+
+impl<'a> Learner for LStarImpl<'a> {
+    fn run_round(&mut self) -> Result<RoundResult> {
+        loop {
+            if let ClosednessCheckResult::UnclosedPrefix(prefix) = self.check_closedness() {
+                self.table.insert_prefix(&prefix)?;
+                self.closedness_repairs += 1;
+                continue;
+            }
+
+            if let ConsistencyCheckResult::DistinguishingSuffix(suffix) = self.check_consistency() {
+                self.table.insert_suffix(&suffix)?;
+                self.consistency_repairs += 1;
+                continue;
+            }
+
+            let dfa = self.build_dfa();
+
+            if let EquivalenceCheckResult::Counterexample(word) =
+                self.mat.check_equivalence(&dfa)?
+            {
+                self.insert_prefix_recursive(&word)?;
+                break Ok(RoundResult::Counterexample(word));
+            }
+
+            break Ok(RoundResult::Finished(Box::new(dfa)));
+        }
+    }
+
+    fn as_table_learner(&self) -> Option<&dyn TableLearner> {
+        Some(self)
+    }
+}
+
+impl<'a> TableLearner for LStarImpl<'a> {
+    fn get_table_statistics(&self) -> TableStatistics {
+        TableStatistics {
+            closedness_repairs: self.closedness_repairs,
+            consistency_repairs: self.consistency_repairs,
+            size: TableSize {
+                upper: self.table.prefixes.len(),
+                lower: self.table.extended_prefixes.len(),
+                suffixes: self.table.suffixes.len(),
+            },
+        }
+    }
+}
+
+enum ClosednessCheckResult {
+    Ok,
+    UnclosedPrefix(String),
+}
+
+enum ConsistencyCheckResult {
+    Ok,
+    DistinguishingSuffix(String),
+}
+
+impl<'a> LStarImpl<'a> {
+    pub fn new(mat: &'a dyn Mat) -> Result<Self> {
+        Ok(Self {
+            mat,
+            table: ObservationTable::new(mat)?,
+            closedness_repairs: 0,
+            consistency_repairs: 0,
+        })
+    }
+
+    fn insert_prefix_recursive(&mut self, word: &str) -> Result<()> {
+        for (index, _) in word.char_indices().skip(1) {
+            self.table.insert_prefix(&word[..index])?;
+        }
+        self.table.insert_prefix(word)
+    }
+
+    fn check_closedness(&self) -> ClosednessCheckResult {
+        for extended_prefix in &self.table.extended_prefixes {
+            let row = self.table.get_row(extended_prefix);
+            if !self
+                .table
+                .prefixes
+                .iter()
+                .any(|prefix| self.table.get_row(prefix) == row)
+            {
+                return ClosednessCheckResult::UnclosedPrefix(extended_prefix.to_owned());
+            }
+        }
+
+        ClosednessCheckResult::Ok
+    }
+
+    fn check_consistency(&self) -> ConsistencyCheckResult {
+        for (i, prefix_1) in self.table.prefixes.iter().enumerate() {
+            for prefix_2 in self.table.prefixes.iter().skip(i + 1) {
+                if self.table.get_row(prefix_1) != self.table.get_row(prefix_2) {
+                    continue;
+                }
+
+                for letter in self.mat.get_alphabet().chars() {
+                    let row_1 = self.table.get_row(&format!("{prefix_1}{letter}"));
+                    let row_2 = self.table.get_row(&format!("{prefix_2}{letter}"));
+
+                    if let Some(index) = (0..row_1.len()).find(|index| row_1[*index] != row_2[*index])
+                    {
+                        let suffix = &self.table.suffixes[index];
+                        return ConsistencyCheckResult::DistinguishingSuffix(format!(
+                            "{letter}{suffix}"
+                        ));
+                    }
+                }
+            }
+        }
+
+        ConsistencyCheckResult::Ok
+    }
+
+    fn build_dfa(&self) -> AutomataImpl {
+        // Состояния -- различные строки верхней части таблицы, ε задаёт стартовое
+        let mut row_to_state = HashMap::<&Vec<bool>, usize>::new();
+        let mut state_prefixes = Vec::new();
+        for prefix in &self.table.prefixes {
+            let row = self.table.get_row(prefix);
+            if !row_to_state.contains_key(row) {
+                row_to_state.insert(row, state_prefixes.len());
+                state_prefixes.push(prefix);
+            }
+        }
+
+        let mut automata = AutomataImpl::new(state_prefixes.len());
+        for (state, prefix) in state_prefixes.iter().enumerate() {
+            for letter in self.mat.get_alphabet().chars() {
+                let row = self.table.get_row(&format!("{prefix}{letter}"));
+                let next_state = row_to_state.get(row).unwrap();
+                automata.transitions[state][*next_state].insert(letter.to_string());
+            }
+
+            // Первый столбец соответствует пустому суффиксу
+            automata.finite_states[state] = self.table.get_row(prefix)[0];
+        }
+
+        debug_assert_eq!(row_to_state.get(self.table.get_row("")), Some(&START));
+        automata
+    }
+}
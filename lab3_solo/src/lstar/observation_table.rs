@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+use crate::automata::EPSILON;
+use crate::error::Result;
+use crate::mat::Mat;
+
+pub struct ObservationTable<'a> {
+    mat: &'a dyn Mat,
+    pub prefixes: Vec<String>,
+    pub extended_prefixes: Vec<String>,
+    pub suffixes: Vec<String>,
+    pub prefix_to_row: HashMap<String, Vec<bool>>,
+}
+
+impl<'a> ObservationTable<'a> {
+    pub fn new(mat: &'a dyn Mat) -> Result<Self> {
+        let mut table = Self {
+            mat,
+            prefixes: Vec::new(),
+            extended_prefixes: Vec::new(),
+            suffixes: vec![EPSILON.to_owned()],
+            prefix_to_row: HashMap::new(),
+        };
+
+        table.insert_prefix(EPSILON)?;
+
+        Ok(table)
+    }
+
+    pub fn get_row(&self, prefix: &str) -> &Vec<bool> {
+        self.prefix_to_row.get(prefix).unwrap()
+    }
+
+    pub fn insert_prefix(&mut self, prefix: &str) -> Result<()> {
+        if self.prefixes.iter().any(|other| other == prefix) {
+            return Ok(());
+        }
+
+        // Все строки запрашиваются до изменения таблицы, чтобы ошибка учителя не
+        // оставила в ней префиксов без данных
+        let extensions: Vec<String> = self
+            .mat
+            .get_alphabet()
+            .chars()
+            .map(|letter| format!("{prefix}{letter}"))
+            .filter(|extension| !self.prefix_to_row.contains_key(extension))
+            .collect();
+        let mut rows = Vec::new();
+        for new_prefix in std::iter::once(prefix).chain(extensions.iter().map(String::as_str)) {
+            if !self.prefix_to_row.contains_key(new_prefix) {
+                rows.push((new_prefix.to_owned(), self.query_row(new_prefix)?));
+            }
+        }
+
+        self.prefix_to_row.extend(rows);
+        self.extended_prefixes.retain(|other| other != prefix);
+        self.prefixes.push(prefix.to_owned());
+        self.extended_prefixes.extend(extensions);
+
+        Ok(())
+    }
+
+    pub fn insert_suffix(&mut self, suffix: &str) -> Result<()> {
+        if self.suffixes.iter().any(|other| other == suffix) {
+            return Ok(());
+        }
+
+        // Строки обходятся в порядке добавления, чтобы порядок запросов не
+        // зависел от запуска
+        let prefixes: Vec<String> = self
+            .prefixes
+            .iter()
+            .chain(&self.extended_prefixes)
+            .cloned()
+            .collect();
+        let words: Vec<String> = prefixes
+            .iter()
+            .map(|prefix| format!("{prefix}{suffix}"))
+            .collect();
+        let memberships = self.mat.check_membership_batch(&words)?;
+
+        self.suffixes.push(suffix.to_owned());
+        for (prefix, is_member) in prefixes.iter().zip(memberships) {
+            self.prefix_to_row.get_mut(prefix).unwrap().push(is_member);
+        }
+
+        Ok(())
+    }
+
+    fn query_row(&self, prefix: &str) -> Result<Vec<bool>> {
+        let words: Vec<String> = self
+            .suffixes
+            .iter()
+            .map(|suffix| format!("{prefix}{suffix}"))
+            .collect();
+        self.mat.check_membership_batch(&words)
+    }
+}
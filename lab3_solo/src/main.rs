@@ -1,30 +1,507 @@
-pub mod automata;
-pub mod mat;
-pub mod nl;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+use std::process;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
 
-use clap::Parser;
-use nl::Nl;
+use clap::{Parser, ValueEnum};
 
-use crate::automata::AutomataImpl;
-use crate::mat::MatImpl;
-use crate::nl::NlImpl;
+use lab3_solo::automata::AutomataImpl;
+use lab3_solo::checkpoint::{Checkpoint, CheckpointConfig};
+use lab3_solo::error::{Error, Result};
+use lab3_solo::events::{EventFormat, EventLog, Verbosity};
+use lab3_solo::learner::{self, LearnerConfig, Limits};
+use lab3_solo::mat::equivalence::EquivalenceMode;
+use lab3_solo::mat::human::{HumanMat, HypothesisFormat};
+use lab3_solo::mat::oracle::{OracleOptions, Verdict, WordEncoding, WordInput};
+use lab3_solo::mat::reference::ReferenceMat;
+use lab3_solo::mat::transcript::{RecordingMat, ReplayMat};
+use lab3_solo::mat::{Mat, MatImpl};
+use lab3_solo::nl::counterexample::CounterexampleStrategy;
+use lab3_solo::nl::render::TableFormat;
+use lab3_solo::nl::seed::Seed;
+
+#[derive(Clone, ValueEnum)]
+enum Equivalence {
+    Shortlex,
+    W,
+    Wp,
+}
+
+#[derive(Clone, ValueEnum)]
+enum Teacher {
+    Oracle,
+    Automaton,
+    Regex,
+    // Ответы берутся из протокола, записанного с --record
+    Replay,
+    // Ответы даёт человек в терминале
+    Human,
+}
+
+#[derive(Clone, ValueEnum)]
+enum HypothesisView {
+    Transitions,
+    Regex,
+}
+
+#[derive(Clone, ValueEnum)]
+enum Input {
+    Argv,
+    Stdin,
+    File,
+}
+
+#[derive(Clone, ValueEnum)]
+enum Encoding {
+    Raw,
+    Hex,
+    Symbols,
+}
+
+#[derive(Clone, ValueEnum)]
+enum VerdictSource {
+    Output,
+    ExitCode,
+}
+
+#[derive(Clone, ValueEnum)]
+enum Counterexamples {
+    AllPrefixes,
+    AllSuffixes,
+    RivestSchapire,
+    MalerPnueli,
+}
+
+#[derive(Clone, ValueEnum)]
+enum Algorithm {
+    Nl,
+    Lstar,
+    Kv,
+    Ttt,
+}
+
+#[derive(Clone, PartialEq, ValueEnum)]
+enum Output {
+    Rfsa,
+    Dfa,
+    MinDfa,
+    All,
+}
+
+#[derive(Clone, ValueEnum)]
+enum Table {
+    Text,
+    Csv,
+    Latex,
+}
+
+#[derive(Clone, ValueEnum)]
+enum Report {
+    Text,
+    Json,
+}
+
+#[derive(Clone, ValueEnum)]
+enum LogVerbosity {
+    Quiet,
+    Info,
+    Debug,
+}
+
+#[derive(Clone, ValueEnum)]
+enum LogFormat {
+    Text,
+    Json,
+}
 
 #[derive(Parser)]
 struct Cli {
     alphabet: String,
-    oracle_path: String,
+    /// Путь к оракулу, путь к файлу автомата, регулярное выражение или путь к протоколу (см. --teacher);
+    /// при --teacher human не используется
+    target: String,
     max_tests: usize,
     word_max_len: usize,
+
+    #[arg(long, value_enum, default_value_t = Teacher::Oracle)]
+    teacher: Teacher,
+
+    /// Вид гипотезы, показываемой человеку при --teacher human
+    #[arg(long, value_enum, default_value_t = HypothesisView::Transitions)]
+    hypothesis_view: HypothesisView,
+
+    /// Записать все запросы к учителю и ответы на них в файл
+    #[arg(long)]
+    record: Option<String>,
+
+    /// Алгоритм обучения
+    #[arg(long, value_enum, default_value_t = Algorithm::Nl)]
+    algorithm: Algorithm,
+
+    /// Выводимый автомат: остаточный (только NL*), ДКА, минимальный ДКА или все
+    #[arg(long, value_enum, default_value_t = Output::Dfa)]
+    output: Output,
+
+    /// Подробность журнала обучения в stderr: info -- исправления таблицы и контрпримеры,
+    /// debug -- вдобавок таблица и гипотеза каждого шага
+    #[arg(long, value_enum, default_value_t = LogVerbosity::Quiet)]
+    verbosity: LogVerbosity,
+
+    /// Формат журнала: текст или JSON по строке на событие
+    #[arg(long, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
+    /// Напечатать итоговую таблицу наблюдений NL* в заданном формате
+    #[arg(long, value_enum)]
+    table: Option<Table>,
+
+    /// Формат итоговой статистики обучения
+    #[arg(long, value_enum, default_value_t = Report::Text)]
+    report: Report,
+
+    /// Добавить в статистику время учителя и ученика; оно меняется от запуска к запуску,
+    /// поэтому по умолчанию не печатается
+    #[arg(long)]
+    timings: bool,
+
+    /// Остановить обучение после стольких запросов принадлежности
+    #[arg(long)]
+    max_membership_queries: Option<usize>,
+
+    /// Остановить обучение после стольких раундов
+    #[arg(long)]
+    max_rounds: Option<usize>,
+
+    /// Остановить обучение, если гипотеза содержит больше состояний
+    #[arg(long)]
+    max_states: Option<usize>,
+
+    /// Остановить обучение через столько секунд
+    #[arg(long)]
+    time_limit: Option<u64>,
+
+    /// Остановить обучение, если столько раундов подряд гипотеза росла, а
+    /// контрпримеры удлинялись
+    #[arg(long)]
+    max_growing_rounds: Option<usize>,
+
+    /// Файл контрольной точки NL*: состояние сохраняется каждые --checkpoint-interval раундов,
+    /// по Ctrl-C и после ошибки учителя; если файл уже есть, предлагается продолжить с него
+    #[arg(long)]
+    checkpoint: Option<String>,
+
+    /// Число раундов между сохранениями контрольной точки (0 -- только при остановке)
+    #[arg(long, default_value_t = 1)]
+    checkpoint_interval: usize,
+
+    /// Продолжить с контрольной точки без вопроса
+    #[arg(long, conflicts_with = "restart")]
+    resume: bool,
+
+    /// Начать обучение заново, перезаписав имеющуюся контрольную точку
+    #[arg(long)]
+    restart: bool,
+
+    /// Начать NL* со слов доступа и различающих суффиксов автомата из файла
+    /// (в формате --teacher automaton, например сохранённого --save-hypothesis)
+    #[arg(long)]
+    seed_automaton: Option<String>,
+
+    /// Файл слов с известными ответами (`+ слово` или `- слово`); перед обучением
+    /// они сверяются с учителем, а потом NL* о них не спрашивает
+    #[arg(long)]
+    seed_sample: Option<String>,
+
+    /// Записать итоговый ДКА в файл в формате --teacher automaton
+    #[arg(long)]
+    save_hypothesis: Option<String>,
+
+    /// Способ обработки контрпримеров в NL*
+    #[arg(long, value_enum, default_value_t = Counterexamples::AllSuffixes)]
+    counterexamples: Counterexamples,
+
+    /// Укорачивать контрпримеры запросами принадлежности перед обработкой
+    #[arg(long)]
+    shorten_counterexamples: bool,
+
+    #[arg(long, value_enum, default_value_t = Equivalence::Shortlex)]
+    equivalence: Equivalence,
+
+    /// Верхняя оценка числа лишних состояний целевого автомата для W- и Wp-методов
+    #[arg(long, default_value_t = 1)]
+    extra_states: usize,
+
+    /// Способ передачи слова оракулу
+    #[arg(long, value_enum, default_value_t = Input::Argv)]
+    input: Input,
+
+    #[arg(long, value_enum, default_value_t = Encoding::Raw)]
+    encoding: Encoding,
+
+    /// Откуда берётся ответ оракула: из вывода или из кода завершения (0 -- да, 1 -- нет)
+    #[arg(long, value_enum, default_value_t = VerdictSource::Output)]
+    verdict: VerdictSource,
+
+    /// Вывод оракула для слов языка
+    #[arg(long, default_value = "1")]
+    accept: String,
+
+    /// Вывод оракула для слов вне языка
+    #[arg(long, default_value = "0")]
+    reject: String,
+
+    /// Предельное время одного запуска оракула в миллисекундах
+    #[arg(long)]
+    timeout: Option<u64>,
+
+    /// Число повторных запусков оракула после ошибки
+    #[arg(long, default_value_t = 0)]
+    retries: usize,
+
+    /// Пауза перед повторным запуском в миллисекундах
+    #[arg(long, default_value_t = 0)]
+    retry_delay: u64,
+
+    /// Число одновременно запущенных оракулов
+    #[arg(long, default_value_t = 1)]
+    jobs: usize,
 }
 
 fn main() {
-    let args = Cli::parse();
+    if let Err(error) = run(Cli::parse()) {
+        eprintln!("error: {error}");
+        process::exit(1);
+    }
+}
+
+fn run(args: Cli) -> Result<()> {
+    let seed = read_seed(&args)?;
+
+    let equivalence_mode = match args.equivalence {
+        Equivalence::Shortlex => EquivalenceMode::Shortlex {
+            max_tests: args.max_tests,
+            word_max_len: args.word_max_len,
+        },
+        Equivalence::W => EquivalenceMode::W {
+            extra_states: args.extra_states,
+        },
+        Equivalence::Wp => EquivalenceMode::Wp {
+            extra_states: args.extra_states,
+        },
+    };
+
+    let oracle_options = OracleOptions {
+        input: match args.input {
+            Input::Argv => WordInput::Argv,
+            Input::Stdin => WordInput::Stdin,
+            Input::File => WordInput::File,
+        },
+        encoding: match args.encoding {
+            Encoding::Raw => WordEncoding::Raw,
+            Encoding::Hex => WordEncoding::Hex,
+            Encoding::Symbols => WordEncoding::Symbols,
+        },
+        verdict: match args.verdict {
+            VerdictSource::Output => Verdict::Output {
+                accept: args.accept,
+                reject: args.reject,
+            },
+            VerdictSource::ExitCode => Verdict::ExitCode,
+        },
+        timeout: args.timeout.map(Duration::from_millis),
+        retries: args.retries,
+        retry_delay: Duration::from_millis(args.retry_delay),
+        jobs: args.jobs,
+    };
+
+    let mat: Box<dyn Mat> = match args.teacher {
+        Teacher::Oracle => Box::new(MatImpl::new(
+            &args.alphabet,
+            &args.target,
+            oracle_options,
+            equivalence_mode,
+        )),
+        Teacher::Automaton => Box::new(ReferenceMat::from_file(&args.alphabet, &args.target)?),
+        Teacher::Regex => Box::new(ReferenceMat::from_regex(&args.alphabet, &args.target)?),
+        Teacher::Replay => Box::new(ReplayMat::from_file(&args.target)?),
+        Teacher::Human => Box::new(HumanMat::new(
+            &args.alphabet,
+            match args.hypothesis_view {
+                HypothesisView::Transitions => HypothesisFormat::Transitions,
+                HypothesisView::Regex => HypothesisFormat::Regex,
+            },
+        )),
+    };
+    let mat: Box<dyn Mat + '_> = match &args.record {
+        Some(path) => Box::new(RecordingMat::new(mat.as_ref(), path)?),
+        None => mat,
+    };
+    let config = LearnerConfig {
+        algorithm: match args.algorithm {
+            Algorithm::Nl => learner::Algorithm::Nl,
+            Algorithm::Lstar => learner::Algorithm::LStar,
+            Algorithm::Kv => learner::Algorithm::Kv,
+            Algorithm::Ttt => learner::Algorithm::Ttt,
+        },
+        counterexample_strategy: match args.counterexamples {
+            Counterexamples::AllPrefixes => CounterexampleStrategy::AllPrefixes,
+            Counterexamples::AllSuffixes => CounterexampleStrategy::AllSuffixes,
+            Counterexamples::RivestSchapire => CounterexampleStrategy::RivestSchapire,
+            Counterexamples::MalerPnueli => CounterexampleStrategy::MalerPnueli,
+        },
+        shorten_counterexamples: args.shorten_counterexamples,
+        event_log: EventLog::new(
+            match args.verbosity {
+                LogVerbosity::Quiet => Verbosity::Quiet,
+                LogVerbosity::Info => Verbosity::Info,
+                LogVerbosity::Debug => Verbosity::Debug,
+            },
+            match args.log_format {
+                LogFormat::Text => EventFormat::Text,
+                LogFormat::Json => EventFormat::Json,
+            },
+        ),
+        checkpoint: match &args.checkpoint {
+            Some(path) => Some(CheckpointConfig {
+                path: path.to_owned(),
+                interval: args.checkpoint_interval,
+                interrupted: handle_interrupts()?,
+            }),
+            None => None,
+        },
+        seed,
+        limits: Limits {
+            membership_queries: args.max_membership_queries,
+            rounds: args.max_rounds,
+            states: args.max_states,
+            time: args.time_limit.map(Duration::from_secs),
+            growing_rounds: args.max_growing_rounds,
+        },
+    };
+
+    let checkpoint = match &args.checkpoint {
+        Some(path) if Path::new(path).exists() && !args.restart => {
+            let checkpoint = Checkpoint::load(path)?;
+            // Без терминала ответить некому, а начать заново значило бы затереть
+            // состояние прошлого запуска
+            if !args.resume && !io::stdin().is_terminal() {
+                return Err(Error::Checkpoint(format!(
+                    "{path} already exists; pass --resume to continue from it or --restart to \
+                     overwrite it"
+                )));
+            }
+            let question = format!(
+                "Found checkpoint {path} after {} rounds. Continue from it? [y/n] ",
+                checkpoint.rounds
+            );
+            (args.resume || ask_yes(&question)?).then_some(checkpoint)
+        }
+        _ => None,
+    };
+    let result = match checkpoint {
+        Some(checkpoint) => learner::resume(&config, mat.as_ref(), checkpoint)?,
+        None => learner::learn(&config, mat.as_ref())?,
+    };
+
+    let is_shown = |output: Output| args.output == output || args.output == Output::All;
+    if is_shown(Output::Rfsa) {
+        match &result.rfsa {
+            Some(rfsa) => {
+                println!("rfsa: {:?}", rfsa.automata);
+                for (state, prefix) in rfsa.prime_prefixes.iter().enumerate() {
+                    println!("state {state}: {prefix:?}");
+                }
+            }
+            None => println!("rfsa: not built by this algorithm"),
+        }
+    }
+    if is_shown(Output::Dfa) {
+        println!("dfa: {:?}", &result.hypothesis);
+    }
+    if is_shown(Output::MinDfa) {
+        println!("min-dfa: {:?}", result.hypothesis.minimize(&args.alphabet));
+    }
+    if let Some(table) = args.table {
+        let format = match table {
+            Table::Text => TableFormat::Text,
+            Table::Csv => TableFormat::Csv,
+            Table::Latex => TableFormat::Latex,
+        };
+        match &result.table {
+            Some(view) => print!("{}", view.render(format)),
+            None => println!("table: not built by this algorithm"),
+        }
+    }
+    if let Some(path) = &args.save_hypothesis {
+        write_file(path, &result.hypothesis.render_file())?;
+    }
+    match args.report {
+        Report::Text => print!("{}", result.statistics.render_text(args.timings)),
+        Report::Json => println!("{}", result.statistics.render_json(args.timings)),
+    }
+    // Отчёт о признаках нерегулярности печатается отдельно, в JSON -- следующей строкой
+    if let Some(diagnostics) = &result.diagnostics {
+        match args.report {
+            Report::Text => print!("{}", diagnostics.render_text()),
+            Report::Json => println!("{}", serde_json::to_string(diagnostics).unwrap()),
+        }
+    }
+
+    Ok(())
+}
 
-    let mat = MatImpl::new(&args.alphabet, &args.oracle_path, args.max_tests, args.word_max_len);
-    let mut nl = NlImpl::new(&mat);
+// Первый Ctrl-C просит остановить обучение с сохранением состояния, второй
+// завершает программу сразу
+fn handle_interrupts() -> Result<Arc<AtomicBool>> {
+    use signal_hook::consts::SIGINT;
+    use signal_hook::flag;
 
-    let dfa = nl.get_dfa();
-    let dfa_impl = dfa.as_any().downcast_ref::<AutomataImpl>().unwrap();
+    let interrupted = Arc::new(AtomicBool::new(false));
+    flag::register_conditional_shutdown(SIGINT, 1, interrupted.clone())
+        .and_then(|_| flag::register(SIGINT, interrupted.clone()))
+        .map_err(|error| Error::Checkpoint(format!("cannot handle Ctrl-C: {error}")))?;
+
+    Ok(interrupted)
+}
+
+fn ask_yes(question: &str) -> Result<bool> {
+    let to_error = |source| Error::Io {
+        path: "stdin".to_string(),
+        source,
+    };
+    eprint!("{question}");
+    io::stderr().flush().map_err(to_error)?;
+
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line).map_err(to_error)?;
+    Ok(matches!(line.trim(), "y" | "yes"))
+}
+
+fn read_seed(args: &Cli) -> Result<Seed> {
+    let mut seed = Seed::default();
+    if let Some(path) = &args.seed_automaton {
+        let automata = AutomataImpl::parse(&read_file(path)?).map_err(Error::Parse)?;
+        seed = seed.merge(Seed::from_automata(&automata, &args.alphabet));
+    }
+    if let Some(path) = &args.seed_sample {
+        seed = seed.merge(Seed::from_sample(&read_file(path)?, &args.alphabet)?);
+    }
+
+    Ok(seed)
+}
+
+fn read_file(path: &str) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|source| Error::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
 
-    println!("{:?}", &dfa_impl);
+fn write_file(path: &str, text: &str) -> Result<()> {
+    std::fs::write(path, text).map_err(|source| Error::Io {
+        path: path.to_owned(),
+        source,
+    })
 }
@@ -1,37 +1,18 @@
 #![allow(dead_code)]
 
-use std::process::Command;
-
-use once_cell::sync::Lazy;
+pub mod closure;
+pub mod conformance;
+pub mod counting;
+pub mod equivalence;
+pub mod human;
+pub mod oracle;
+pub mod reference;
+pub mod transcript;
 
 use crate::automata::Automata;
-
-static mut LAST_TEST_WORD: usize = 0;
-static mut TEST_WORDS: Lazy<Vec<String>> = Lazy::new(|| vec!["".to_string()]);
-
-fn get_next_word(mat: &dyn Mat) -> String {
-    unsafe {
-        let next_word = TEST_WORDS[LAST_TEST_WORD].to_owned();
-        LAST_TEST_WORD += 1;
-
-        if LAST_TEST_WORD == TEST_WORDS.len() {
-            let alphabet = mat.get_alphabet();
-            let mut extension = Vec::with_capacity(TEST_WORDS.len() * alphabet.len());
-
-            for word in TEST_WORDS.iter() {
-                for letter in alphabet.chars() {
-                    extension.push(format!("{word}{letter}"));
-                }
-            }
-
-            TEST_WORDS.clear();
-            TEST_WORDS.append(&mut extension);
-            LAST_TEST_WORD = 0;
-        }
-
-        next_word
-    }
-}
+use crate::error::Result;
+use crate::mat::equivalence::{EquivalenceMode, EquivalenceTester};
+use crate::mat::oracle::{Oracle, OracleOptions};
 
 pub enum EquivalenceCheckResult {
     Ok,
@@ -39,54 +20,90 @@ pub enum EquivalenceCheckResult {
 }
 
 pub trait Mat {
-    fn check_membership(&self, word: &str) -> bool;
+    fn check_membership(&self, word: &str) -> Result<bool>;
 
-    fn check_equivalence(&self, automata: &dyn Automata) -> EquivalenceCheckResult;
+    // Запросы пакета независимы, поэтому реализации могут выполнять их параллельно
+    fn check_membership_batch(&self, words: &[String]) -> Result<Vec<bool>> {
+        words.iter().map(|word| self.check_membership(word)).collect()
+    }
+
+    fn check_equivalence(&self, automata: &dyn Automata) -> Result<EquivalenceCheckResult>;
+
+    // Запрос эквивалентности, тесты которого отправляются учителю mat -- обёртке
+    // над этим учителем, чтобы обёртки видели и запросы тестирования. Точные
+    // учителя запросов принадлежности при этом не делают.
+    fn check_equivalence_through(
+        &self,
+        automata: &dyn Automata,
+        _mat: &dyn Mat,
+    ) -> Result<EquivalenceCheckResult> {
+        self.check_equivalence(automata)
+    }
 
     fn get_alphabet(&self) -> String;
+
+    // Положение учителя в последовательности запросов эквивалентности,
+    // сохраняемое в контрольной точке
+    fn get_equivalence_position(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    fn set_equivalence_position(&self, _position: serde_json::Value) -> Result<()> {
+        Ok(())
+    }
 }
 
 pub struct MatImpl {
     alphabet: String,
-    oracle_path: String,
-    max_tests: usize,
-    word_max_len: usize,
+    oracle: Oracle,
+    equivalence_tester: EquivalenceTester,
 }
 
 impl Mat for MatImpl {
-    fn check_membership(&self, word: &str) -> bool {
-        let output = Command::new(&self.oracle_path).arg(word).output().unwrap();
-        String::from_utf8(output.stdout).unwrap().eq("1\n")
+    fn check_membership(&self, word: &str) -> Result<bool> {
+        self.oracle.query(word)
     }
 
-    fn check_equivalence(&self, automata: &dyn Automata) -> EquivalenceCheckResult {
-        for _ in 0..self.max_tests {
-            let word = get_next_word(self);
-            if word.len().ge(&self.word_max_len) {
-                dbg!("Equivalence check has passed, because max_word_len has been reached");
-                return EquivalenceCheckResult::Ok;
-            }
-
-            if self.check_membership(&word) && !automata.check_membership(&word) {
-                return EquivalenceCheckResult::Counterexample(word);
-            }
-        }
+    fn check_membership_batch(&self, words: &[String]) -> Result<Vec<bool>> {
+        self.oracle.query_batch(words)
+    }
 
-        EquivalenceCheckResult::Ok
+    fn check_equivalence(&self, automata: &dyn Automata) -> Result<EquivalenceCheckResult> {
+        self.equivalence_tester.find_counterexample(automata, self)
+    }
+
+    fn check_equivalence_through(
+        &self,
+        automata: &dyn Automata,
+        mat: &dyn Mat,
+    ) -> Result<EquivalenceCheckResult> {
+        self.equivalence_tester.find_counterexample(automata, mat)
     }
 
     fn get_alphabet(&self) -> String {
         self.alphabet.to_string()
     }
+
+    fn get_equivalence_position(&self) -> Option<serde_json::Value> {
+        self.equivalence_tester.get_position()
+    }
+
+    fn set_equivalence_position(&self, position: serde_json::Value) -> Result<()> {
+        self.equivalence_tester.set_position(position, &self.alphabet)
+    }
 }
 
 impl MatImpl {
-    pub fn new(alphabet: &str, oracle_path: &str, max_tests: usize, word_max_len: usize) -> Self {
+    pub fn new(
+        alphabet: &str,
+        oracle_path: &str,
+        oracle_options: OracleOptions,
+        equivalence_mode: EquivalenceMode,
+    ) -> Self {
         Self {
             alphabet: alphabet.to_owned(),
-            oracle_path: oracle_path.to_owned(),
-            max_tests,
-            word_max_len,
+            oracle: Oracle::new(oracle_path, oracle_options),
+            equivalence_tester: EquivalenceTester::new(equivalence_mode),
         }
     }
 }
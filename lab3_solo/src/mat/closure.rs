@@ -0,0 +1,60 @@
+use crate::automata::{Automata, Symbol};
+use crate::error::Result;
+use crate::mat::equivalence::{EquivalenceMode, EquivalenceTester};
+use crate::mat::{EquivalenceCheckResult, Mat};
+
+// Учитель, отвечающий на запросы принадлежности функцией из той же программы
+pub struct ClosureMat<F: Fn(&str) -> bool> {
+    alphabet: String,
+    predicate: F,
+    equivalence_tester: EquivalenceTester,
+}
+
+impl<F: Fn(&str) -> bool> Mat for ClosureMat<F> {
+    fn check_membership(&self, word: &str) -> Result<bool> {
+        Ok((self.predicate)(word))
+    }
+
+    fn check_equivalence(&self, automata: &dyn Automata) -> Result<EquivalenceCheckResult> {
+        self.equivalence_tester.find_counterexample(automata, self)
+    }
+
+    fn check_equivalence_through(
+        &self,
+        automata: &dyn Automata,
+        mat: &dyn Mat,
+    ) -> Result<EquivalenceCheckResult> {
+        self.equivalence_tester.find_counterexample(automata, mat)
+    }
+
+    fn get_alphabet(&self) -> String {
+        self.alphabet.to_string()
+    }
+
+    fn get_equivalence_position(&self) -> Option<serde_json::Value> {
+        self.equivalence_tester.get_position()
+    }
+
+    fn set_equivalence_position(&self, position: serde_json::Value) -> Result<()> {
+        self.equivalence_tester.set_position(position, &self.alphabet)
+    }
+}
+
+impl<F: Fn(&str) -> bool> ClosureMat<F> {
+    pub fn new(alphabet: &str, predicate: F, equivalence_mode: EquivalenceMode) -> Self {
+        Self {
+            alphabet: alphabet.to_owned(),
+            predicate,
+            equivalence_tester: EquivalenceTester::new(equivalence_mode),
+        }
+    }
+}
+
+pub fn from_symbols(
+    alphabet: &str,
+    predicate: impl Fn(&[Symbol]) -> bool,
+    equivalence_mode: EquivalenceMode,
+) -> ClosureMat<impl Fn(&str) -> bool> {
+    let predicate = move |word: &str| predicate(&word.chars().collect::<Vec<Symbol>>());
+    ClosureMat::new(alphabet, predicate, equivalence_mode)
+}
@@ -0,0 +1,299 @@
+use std::collections::BTreeSet;
+
+use crate::automata::{compare_shortlex, AutomataImpl, START};
+
+// Таблица переходов полного ДКА, построенного по гипотезе
+struct CompleteDfa {
+    transitions: Vec<Vec<usize>>,
+    finite_states: Vec<bool>,
+}
+
+impl CompleteDfa {
+    fn new(automata: &AutomataImpl, alphabet: &str) -> Self {
+        let transitions = (0..automata.size)
+            .map(|state| {
+                alphabet
+                    .chars()
+                    .map(|letter| automata.get_next_state(state, letter).unwrap())
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            transitions,
+            finite_states: automata.finite_states.clone(),
+        }
+    }
+
+    fn get_state(&self, word: &str, alphabet: &str) -> usize {
+        let mut state = START;
+        for letter in word.chars() {
+            let index = alphabet.chars().position(|c| c == letter).unwrap();
+            state = self.transitions[state][index];
+        }
+        state
+    }
+}
+
+pub struct Characterization {
+    dfa: CompleteDfa,
+    // Слова, по которым впервые достигается каждое достижимое состояние
+    access_words: Vec<(usize, String)>,
+    state_identifiers: Vec<BTreeSet<String>>,
+}
+
+impl Characterization {
+    pub fn new(automata: &AutomataImpl, alphabet: &str) -> Self {
+        let automata = automata.complete(alphabet);
+        let dfa = CompleteDfa::new(&automata, alphabet);
+        let mut access_words: Vec<(usize, String)> = automata
+            .get_access_words(alphabet)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(state, word)| Some((state, word?)))
+            .collect();
+        access_words.sort_by(|(_, first), (_, second)| compare_shortlex(first, second));
+        let states = Vec::from_iter(access_words.iter().map(|(state, _)| *state));
+        let state_identifiers = get_state_identifiers(&dfa, &states, alphabet);
+
+        Self {
+            dfa,
+            access_words,
+            state_identifiers,
+        }
+    }
+
+    pub fn get_state_cover(&self) -> Vec<String> {
+        self.access_words
+            .iter()
+            .map(|(_, word)| word.to_owned())
+            .collect()
+    }
+
+    pub fn get_transition_cover(&self, alphabet: &str) -> Vec<String> {
+        let mut cover = self.get_state_cover();
+        for (_, word) in &self.access_words {
+            for letter in alphabet.chars() {
+                cover.push(format!("{word}{letter}"));
+            }
+        }
+        cover
+    }
+
+    pub fn get_characterization_set(&self) -> BTreeSet<String> {
+        let mut set = BTreeSet::new();
+        for (state, _) in &self.access_words {
+            set.extend(self.get_state_identifier(*state).iter().cloned());
+        }
+        if set.is_empty() {
+            set.insert(String::new());
+        }
+        set
+    }
+
+    fn get_state_identifier(&self, state: usize) -> &BTreeSet<String> {
+        &self.state_identifiers[state]
+    }
+}
+
+// Набор тестов W-метода: P · Σ[k] · W
+pub fn get_w_suite(automata: &AutomataImpl, alphabet: &str, extra_states: usize) -> Vec<String> {
+    let characterization = Characterization::new(automata, alphabet);
+    let middles = get_middles(alphabet, extra_states);
+    let suffixes = characterization.get_characterization_set();
+
+    let mut suite = BTreeSet::new();
+    for prefix in characterization.get_transition_cover(alphabet) {
+        for middle in &middles {
+            for suffix in &suffixes {
+                suite.insert(format!("{prefix}{middle}{suffix}"));
+            }
+        }
+    }
+
+    sort_shortlex(suite)
+}
+
+// Набор тестов Wp-метода: Q · Σ[k] · W ∪ R · Σ^k ⊗ W_i, где R = P \ Q
+pub fn get_wp_suite(automata: &AutomataImpl, alphabet: &str, extra_states: usize) -> Vec<String> {
+    let characterization = Characterization::new(automata, alphabet);
+    let middles = get_middles(alphabet, extra_states);
+    let suffixes = characterization.get_characterization_set();
+
+    let state_cover = characterization.get_state_cover();
+    let mut suite = BTreeSet::new();
+    for prefix in &state_cover {
+        for middle in &middles {
+            for suffix in &suffixes {
+                suite.insert(format!("{prefix}{middle}{suffix}"));
+            }
+        }
+    }
+
+    let state_cover = BTreeSet::from_iter(state_cover);
+    let longest_middles = middles
+        .iter()
+        .filter(|middle| middle.chars().count() == extra_states);
+    let transition_cover = characterization.get_transition_cover(alphabet);
+    let remainder = transition_cover
+        .iter()
+        .filter(|word| !state_cover.contains(*word));
+
+    for prefix in remainder {
+        for middle in longest_middles.clone() {
+            let word = format!("{prefix}{middle}");
+            let state = characterization.dfa.get_state(&word, alphabet);
+            let identifier = characterization.get_state_identifier(state);
+
+            if identifier.is_empty() {
+                suite.insert(word);
+                continue;
+            }
+            for suffix in identifier {
+                suite.insert(format!("{word}{suffix}"));
+            }
+        }
+    }
+
+    sort_shortlex(suite)
+}
+
+// Для каждого состояния -- множество суффиксов, отличающих его от всех
+// неэквивалентных ему достижимых состояний
+fn get_state_identifiers(
+    dfa: &CompleteDfa,
+    reachable_states: &[usize],
+    alphabet: &str,
+) -> Vec<BTreeSet<String>> {
+    let size = dfa.transitions.len();
+    let mut separators = vec![vec![None::<String>; size]; size];
+
+    for (i, row) in separators.iter_mut().enumerate() {
+        for (j, separator) in row.iter_mut().enumerate() {
+            if dfa.finite_states[i] != dfa.finite_states[j] {
+                *separator = Some(String::new());
+            }
+        }
+    }
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for i in 0..size {
+            for j in 0..size {
+                if separators[i][j].is_some() {
+                    continue;
+                }
+
+                for (index, letter) in alphabet.chars().enumerate() {
+                    let next_i = dfa.transitions[i][index];
+                    let next_j = dfa.transitions[j][index];
+                    if let Some(suffix) = &separators[next_i][next_j] {
+                        separators[i][j] = Some(format!("{letter}{suffix}"));
+                        changed = true;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    separators
+        .into_iter()
+        .map(|row| {
+            reachable_states
+                .iter()
+                .filter_map(|state| row[*state].clone())
+                .collect()
+        })
+        .collect()
+}
+
+fn get_middles(alphabet: &str, max_len: usize) -> Vec<String> {
+    let mut middles = vec![String::new()];
+    let mut layer = vec![String::new()];
+
+    for _ in 0..max_len {
+        let mut next_layer = Vec::with_capacity(layer.len() * alphabet.len());
+        for word in &layer {
+            for letter in alphabet.chars() {
+                next_layer.push(format!("{word}{letter}"));
+            }
+        }
+        middles.extend(next_layer.iter().cloned());
+        layer = next_layer;
+    }
+
+    middles
+}
+
+fn sort_shortlex(words: BTreeSet<String>) -> Vec<String> {
+    let mut words = Vec::from_iter(words);
+    words.sort_by(|first, second| compare_shortlex(first, second));
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automata::{get_automata_impl, Automata};
+
+    fn get_minimal_dfa(regex: &str) -> AutomataImpl {
+        let automata = crate::regex::parse(regex).unwrap();
+        get_automata_impl(automata.determinize().as_ref()).minimize("ab")
+    }
+
+    fn is_shortlex_sorted(words: &[String]) -> bool {
+        words
+            .windows(2)
+            .all(|pair| compare_shortlex(&pair[0], &pair[1]) == std::cmp::Ordering::Less)
+    }
+
+    #[test]
+    fn characterizes_minimal_dfa() {
+        let characterization = Characterization::new(&get_minimal_dfa("(a|b)*a"), "ab");
+
+        assert_eq!(characterization.get_state_cover(), ["", "a"]);
+        let transition_cover = BTreeSet::from_iter(characterization.get_transition_cover("ab"));
+        assert_eq!(
+            transition_cover,
+            BTreeSet::from_iter(["", "a", "b", "aa", "ab"].map(String::from))
+        );
+        assert_eq!(
+            characterization.get_characterization_set(),
+            BTreeSet::from(["".into()])
+        );
+    }
+
+    #[test]
+    fn suites_find_faults_within_extra_states() {
+        // Гипотеза из двух состояний, целевой язык требует четырёх
+        let hypothesis = get_minimal_dfa("(a|b)*a");
+        let target = get_minimal_dfa("(a|b)*a(a|b)");
+
+        for suite in [
+            get_w_suite(&hypothesis, "ab", 2),
+            get_wp_suite(&hypothesis, "ab", 2),
+        ] {
+            assert!(is_shortlex_sorted(&suite));
+            assert!(suite
+                .iter()
+                .any(|word| hypothesis.check_membership(word) != target.check_membership(word)));
+        }
+    }
+
+    #[test]
+    fn wp_suite_is_part_of_w_suite() {
+        for regex in ["(a|b)*a", "(ab|ba)*", "a(a|b)*b"] {
+            let automata = get_minimal_dfa(regex);
+            for extra_states in 0..3 {
+                let w_suite = BTreeSet::from_iter(get_w_suite(&automata, "ab", extra_states));
+                let wp_suite = get_wp_suite(&automata, "ab", extra_states);
+                assert!(
+                    wp_suite.iter().all(|word| w_suite.contains(word)),
+                    "{regex}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use crate::automata::{get_automata_impl, Automata, AutomataImpl};
+use crate::error::{Error, Result};
+use crate::learner::{Limit, Limits};
+use crate::mat::{EquivalenceCheckResult, Mat};
+
+// Считает запросы ученика и тестов эквивалентности и время, потраченное
+// учителем, и прерывает обучение ошибкой LimitReached, когда исчерпано ограничение
+pub struct CountingMat<'a> {
+    mat: &'a dyn Mat,
+    limits: Limits,
+    start: Instant,
+    membership_queries: Cell<usize>,
+    // Запросы принадлежности, сделанные при проверке эквивалентности
+    test_membership_queries: Cell<usize>,
+    is_testing: Cell<bool>,
+    queried_words: RefCell<HashSet<String>>,
+    equivalence_queries: Cell<usize>,
+    // Контрпримеры в порядке ответов учителя; запрос, прерванный ограничением,
+    // в них не попадает
+    counterexamples: RefCell<Vec<String>>,
+    answered_equivalence_queries: Cell<usize>,
+    hypothesis_sizes: RefCell<Vec<usize>>,
+    last_hypothesis: RefCell<Option<AutomataImpl>>,
+    oracle_time: Cell<Duration>,
+}
+
+impl<'a> Mat for CountingMat<'a> {
+    fn check_membership(&self, word: &str) -> Result<bool> {
+        self.check_limits(1)?;
+        self.count_membership_queries(1);
+        self.queried_words.borrow_mut().insert(word.to_owned());
+        self.measure(|| self.mat.check_membership(word))
+    }
+
+    fn check_membership_batch(&self, words: &[String]) -> Result<Vec<bool>> {
+        self.check_limits(words.len())?;
+        self.count_membership_queries(words.len());
+        self.queried_words
+            .borrow_mut()
+            .extend(words.iter().cloned());
+        self.measure(|| self.mat.check_membership_batch(words))
+    }
+
+    fn check_equivalence(&self, automata: &dyn Automata) -> Result<EquivalenceCheckResult> {
+        self.check_equivalence_through(automata, self)
+    }
+
+    fn check_equivalence_through(
+        &self,
+        automata: &dyn Automata,
+        mat: &dyn Mat,
+    ) -> Result<EquivalenceCheckResult> {
+        let hypothesis = get_automata_impl(automata);
+        self.last_hypothesis.replace(Some(hypothesis.clone()));
+        if self.limits.states.is_some_and(|max_states| hypothesis.size > max_states) {
+            return Err(Error::LimitReached(Limit::States));
+        }
+        self.hypothesis_sizes.borrow_mut().push(hypothesis.size);
+        self.check_limits(0)?;
+        self.equivalence_queries.set(self.equivalence_queries.get() + 1);
+        let result = self.measure(|| {
+            self.is_testing.set(true);
+            let result = self.mat.check_equivalence_through(automata, mat);
+            self.is_testing.set(false);
+            result
+        })?;
+
+        if let EquivalenceCheckResult::Counterexample(word) = &result {
+            self.counterexamples.borrow_mut().push(word.to_owned());
+        }
+        self.answered_equivalence_queries
+            .set(self.answered_equivalence_queries.get() + 1);
+        Ok(result)
+    }
+
+    fn get_alphabet(&self) -> String {
+        self.mat.get_alphabet()
+    }
+
+    fn get_equivalence_position(&self) -> Option<serde_json::Value> {
+        self.mat.get_equivalence_position()
+    }
+
+    fn set_equivalence_position(&self, position: serde_json::Value) -> Result<()> {
+        self.mat.set_equivalence_position(position)
+    }
+}
+
+impl<'a> CountingMat<'a> {
+    pub fn new(mat: &'a dyn Mat, limits: Limits) -> Self {
+        Self {
+            mat,
+            limits,
+            start: Instant::now(),
+            membership_queries: Cell::new(0),
+            test_membership_queries: Cell::new(0),
+            is_testing: Cell::new(false),
+            queried_words: RefCell::new(HashSet::new()),
+            equivalence_queries: Cell::new(0),
+            counterexamples: RefCell::new(Vec::new()),
+            answered_equivalence_queries: Cell::new(0),
+            hypothesis_sizes: RefCell::new(Vec::new()),
+            last_hypothesis: RefCell::new(None),
+            oracle_time: Cell::new(Duration::ZERO),
+        }
+    }
+
+    // Пакет запросов отправляется, только если он целиком укладывается в ограничение
+    fn check_limits(&self, membership_queries: usize) -> Result<()> {
+        let total = self.membership_queries.get() + membership_queries;
+        if self.limits.membership_queries.is_some_and(|max_queries| total > max_queries) {
+            return Err(Error::LimitReached(Limit::MembershipQueries));
+        }
+        if self.limits.time.is_some_and(|time| self.start.elapsed() >= time) {
+            return Err(Error::LimitReached(Limit::Time));
+        }
+
+        Ok(())
+    }
+
+    fn count_membership_queries(&self, count: usize) {
+        self.membership_queries.set(self.membership_queries.get() + count);
+        if self.is_testing.get() {
+            self.test_membership_queries
+                .set(self.test_membership_queries.get() + count);
+        }
+    }
+
+    // Время запросов тестирования уже входит во время запроса эквивалентности
+    fn measure<T>(&self, query: impl FnOnce() -> T) -> T {
+        if self.is_testing.get() {
+            return query();
+        }
+
+        let start = Instant::now();
+        let result = query();
+        self.oracle_time.set(self.oracle_time.get() + start.elapsed());
+        result
+    }
+
+    pub fn get_membership_queries(&self) -> usize {
+        self.membership_queries.get()
+    }
+
+    pub fn get_test_membership_queries(&self) -> usize {
+        self.test_membership_queries.get()
+    }
+
+    pub fn get_unique_membership_queries(&self) -> usize {
+        self.queried_words.borrow().len()
+    }
+
+    pub fn get_equivalence_queries(&self) -> usize {
+        self.equivalence_queries.get()
+    }
+
+    pub fn get_answered_equivalence_queries(&self) -> usize {
+        self.answered_equivalence_queries.get()
+    }
+
+    pub fn get_counterexamples(&self) -> Vec<String> {
+        self.counterexamples.borrow().clone()
+    }
+
+    // Размеры гипотез в порядке запросов эквивалентности
+    pub fn get_hypothesis_sizes(&self) -> Vec<usize> {
+        self.hypothesis_sizes.borrow().clone()
+    }
+
+    pub fn get_last_hypothesis(&self) -> Option<AutomataImpl> {
+        self.last_hypothesis.borrow().clone()
+    }
+
+    pub fn get_oracle_time(&self) -> Duration {
+        self.oracle_time.get()
+    }
+}
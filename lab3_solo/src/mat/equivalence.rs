@@ -0,0 +1,249 @@
+use std::cell::RefCell;
+
+use serde::{Deserialize, Serialize};
+
+use crate::automata::{get_automata_impl, Automata};
+use crate::error::{Error, Result};
+use crate::mat::conformance;
+use crate::mat::{EquivalenceCheckResult, Mat};
+
+// Тесты W- и Wp-методов отправляются оракулу пакетами такого размера
+const BATCH_SIZE: usize = 64;
+
+pub enum EquivalenceMode {
+    Shortlex {
+        max_tests: usize,
+        word_max_len: usize,
+    },
+    W {
+        extra_states: usize,
+    },
+    Wp {
+        extra_states: usize,
+    },
+}
+
+// Положение генератора тестов: длина очередных слов и номер следующего из них
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct ShortlexPosition {
+    word_len: usize,
+    index: usize,
+}
+
+struct ShortlexGenerator {
+    last_test_word: usize,
+    test_words: Vec<String>,
+}
+
+impl ShortlexGenerator {
+    fn new() -> Self {
+        Self {
+            last_test_word: 0,
+            test_words: vec!["".to_string()],
+        }
+    }
+
+    fn get_next_word(&mut self, alphabet: &str) -> String {
+        let next_word = self.test_words[self.last_test_word].to_owned();
+        self.last_test_word += 1;
+
+        if self.last_test_word == self.test_words.len() {
+            self.extend(alphabet);
+            self.last_test_word = 0;
+        }
+
+        next_word
+    }
+
+    fn extend(&mut self, alphabet: &str) {
+        let mut extension = Vec::with_capacity(self.test_words.len() * alphabet.len());
+
+        for word in &self.test_words {
+            for letter in alphabet.chars() {
+                extension.push(format!("{word}{letter}"));
+            }
+        }
+
+        self.test_words = extension;
+    }
+
+    fn get_position(&self) -> ShortlexPosition {
+        ShortlexPosition {
+            word_len: self.test_words[0].chars().count(),
+            index: self.last_test_word,
+        }
+    }
+
+    fn set_position(&mut self, position: ShortlexPosition, alphabet: &str) {
+        self.test_words = vec!["".to_string()];
+        for _ in 0..position.word_len {
+            self.extend(alphabet);
+        }
+        self.last_test_word = position.index.min(self.test_words.len() - 1);
+    }
+}
+
+pub struct EquivalenceTester {
+    mode: EquivalenceMode,
+    generator: RefCell<ShortlexGenerator>,
+}
+
+impl EquivalenceTester {
+    pub fn new(mode: EquivalenceMode) -> Self {
+        Self {
+            mode,
+            generator: RefCell::new(ShortlexGenerator::new()),
+        }
+    }
+
+    // Только перебор в порядке shortlex зависит от предыдущих запросов
+    pub fn get_position(&self) -> Option<serde_json::Value> {
+        match self.mode {
+            EquivalenceMode::Shortlex { .. } => {
+                let position = self.generator.borrow().get_position();
+                Some(serde_json::to_value(position).unwrap())
+            }
+            _ => None,
+        }
+    }
+
+    pub fn set_position(&self, position: serde_json::Value, alphabet: &str) -> Result<()> {
+        let position = serde_json::from_value(position).map_err(|error| {
+            Error::Checkpoint(format!("invalid equivalence test position: {error}"))
+        })?;
+        self.generator.borrow_mut().set_position(position, alphabet);
+        Ok(())
+    }
+
+    pub fn find_counterexample(
+        &self,
+        automata: &dyn Automata,
+        mat: &dyn Mat,
+    ) -> Result<EquivalenceCheckResult> {
+        let alphabet = &mat.get_alphabet();
+        let suite = match self.mode {
+            EquivalenceMode::Shortlex {
+                max_tests,
+                word_max_len,
+            } => {
+                return self.find_shortlex_counterexample(
+                    automata,
+                    mat,
+                    max_tests,
+                    word_max_len,
+                )
+            }
+            EquivalenceMode::W { extra_states } => {
+                conformance::get_w_suite(get_automata_impl(automata), alphabet, extra_states)
+            }
+            EquivalenceMode::Wp { extra_states } => {
+                conformance::get_wp_suite(get_automata_impl(automata), alphabet, extra_states)
+            }
+        };
+
+        for words in suite.chunks(BATCH_SIZE) {
+            let memberships = mat.check_membership_batch(words)?;
+            for (word, is_member) in words.iter().zip(memberships) {
+                if is_member != automata.check_membership(word) {
+                    return Ok(EquivalenceCheckResult::Counterexample(word.to_owned()));
+                }
+            }
+        }
+
+        Ok(EquivalenceCheckResult::Ok)
+    }
+
+    fn find_shortlex_counterexample(
+        &self,
+        automata: &dyn Automata,
+        mat: &dyn Mat,
+        max_tests: usize,
+        word_max_len: usize,
+    ) -> Result<EquivalenceCheckResult> {
+        let alphabet = mat.get_alphabet();
+        let mut generator = self.generator.borrow_mut();
+
+        for _ in 0..max_tests {
+            let word = generator.get_next_word(&alphabet);
+            // Все слова короче word_max_len проверены
+            if word.len().ge(&word_max_len) {
+                return Ok(EquivalenceCheckResult::Ok);
+            }
+
+            if mat.check_membership(&word)? != automata.check_membership(&word) {
+                return Ok(EquivalenceCheckResult::Counterexample(word));
+            }
+        }
+
+        Ok(EquivalenceCheckResult::Ok)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mat::reference::ReferenceMat;
+
+    fn find_counterexample(
+        mode: EquivalenceMode,
+        target: &str,
+        hypothesis: &str,
+    ) -> Option<String> {
+        let mat = ReferenceMat::from_regex("ab", target).unwrap();
+        let hypothesis = crate::regex::parse(hypothesis).unwrap().determinize();
+        match EquivalenceTester::new(mode)
+            .find_counterexample(hypothesis.as_ref(), &mat)
+            .unwrap()
+        {
+            EquivalenceCheckResult::Counterexample(word) => Some(word),
+            EquivalenceCheckResult::Ok => None,
+        }
+    }
+
+    fn get_modes() -> [EquivalenceMode; 3] {
+        [
+            EquivalenceMode::Shortlex {
+                max_tests: 100,
+                word_max_len: 5,
+            },
+            EquivalenceMode::W { extra_states: 1 },
+            EquivalenceMode::Wp { extra_states: 1 },
+        ]
+    }
+
+    #[test]
+    fn finds_words_missing_from_hypothesis() {
+        for mode in get_modes() {
+            assert_eq!(
+                find_counterexample(mode, "(a|b)*", "a*"),
+                Some("b".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn finds_words_wrongly_accepted_by_hypothesis() {
+        for mode in get_modes() {
+            assert_eq!(
+                find_counterexample(mode, "a*", "(a|b)*"),
+                Some("b".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn accepts_equivalent_hypothesis() {
+        for mode in get_modes() {
+            assert_eq!(find_counterexample(mode, "(a|b)*a", "(a|b)*a|b*a"), None);
+        }
+    }
+
+    #[test]
+    fn stops_shortlex_search_at_word_max_len() {
+        let mode = EquivalenceMode::Shortlex {
+            max_tests: 100,
+            word_max_len: 3,
+        };
+        assert_eq!(find_counterexample(mode, "aaa", "∅"), None);
+    }
+}
@@ -0,0 +1,110 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+use crate::automata::{get_automata_impl, Automata};
+use crate::error::{Error, Result};
+use crate::mat::{EquivalenceCheckResult, Mat};
+
+pub enum HypothesisFormat {
+    Transitions,
+    Regex,
+}
+
+// Учитель-человек: вопросы задаются в stderr, ответы читаются из stdin.
+// Ответы на запросы принадлежности запоминаются, чтобы не спрашивать дважды.
+pub struct HumanMat {
+    alphabet: String,
+    hypothesis_format: HypothesisFormat,
+    memberships: RefCell<HashMap<String, bool>>,
+}
+
+impl Mat for HumanMat {
+    fn check_membership(&self, word: &str) -> Result<bool> {
+        if let Some(answer) = self.memberships.borrow().get(word) {
+            return Ok(*answer);
+        }
+
+        let answer = loop {
+            let line = ask(&format!("Does the language contain {}? [y/n] ", show_word(word)))?;
+            match line.as_str() {
+                "y" | "yes" => break true,
+                "n" | "no" => break false,
+                _ => eprintln!("Please answer y or n."),
+            }
+        };
+
+        self.memberships.borrow_mut().insert(word.to_owned(), answer);
+        Ok(answer)
+    }
+
+    fn check_equivalence(&self, automata: &dyn Automata) -> Result<EquivalenceCheckResult> {
+        let hypothesis = get_automata_impl(automata);
+        match self.hypothesis_format {
+            HypothesisFormat::Transitions => {
+                eprintln!("Hypothesis:\n{}", hypothesis.render_transitions(&self.alphabet))
+            }
+            HypothesisFormat::Regex => {
+                eprintln!("Hypothesis: {}", crate::regex::from_automata(hypothesis))
+            }
+        }
+
+        loop {
+            let line = ask("Is the hypothesis correct? Type \"yes\" or a counterexample (ε for the empty word): ")?;
+            if line == "yes" {
+                return Ok(EquivalenceCheckResult::Ok);
+            }
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let word = if line == "ε" { String::new() } else { line };
+            if word.chars().any(|letter| !self.alphabet.contains(letter)) {
+                eprintln!("The counterexample must be a word over {:?}.", self.alphabet);
+                continue;
+            }
+
+            return Ok(EquivalenceCheckResult::Counterexample(word));
+        }
+    }
+
+    fn get_alphabet(&self) -> String {
+        self.alphabet.to_string()
+    }
+}
+
+impl HumanMat {
+    pub fn new(alphabet: &str, hypothesis_format: HypothesisFormat) -> Self {
+        Self {
+            alphabet: alphabet.to_owned(),
+            hypothesis_format,
+            memberships: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+fn ask(question: &str) -> Result<String> {
+    let to_error = |source| Error::Io {
+        path: "stdin".to_string(),
+        source,
+    };
+
+    eprint!("{question}");
+    io::stderr().flush().map_err(to_error)?;
+
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line).map_err(to_error)? == 0 {
+        return Err(to_error(io::ErrorKind::UnexpectedEof.into()));
+    }
+
+    Ok(line.trim().to_string())
+}
+
+fn show_word(word: &str) -> String {
+    if word.is_empty() {
+        "ε".to_string()
+    } else {
+        format!("{word:?}")
+    }
+}
@@ -0,0 +1,488 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, Result};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+static WORD_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// Способ передачи слова оракулу
+pub enum WordInput {
+    Argv,
+    Stdin,
+    // Слово записывается во временный файл, путь к которому передаётся аргументом
+    File,
+}
+
+pub enum WordEncoding {
+    Raw,
+    // Шестнадцатеричная запись байтов слова в UTF-8
+    Hex,
+    // Символы слова через пробел
+    Symbols,
+}
+
+pub enum Verdict {
+    // Оракул печатает одну из строк
+    Output { accept: String, reject: String },
+    // Код 0 -- слово принадлежит языку, 1 -- не принадлежит, остальные -- ошибка
+    ExitCode,
+}
+
+pub struct OracleOptions {
+    pub input: WordInput,
+    pub encoding: WordEncoding,
+    pub verdict: Verdict,
+    pub timeout: Option<Duration>,
+    // Число повторных запусков оракула после ошибки
+    pub retries: usize,
+    pub retry_delay: Duration,
+    // Число одновременно запущенных оракулов при пакетных запросах
+    pub jobs: usize,
+}
+
+impl Default for OracleOptions {
+    fn default() -> Self {
+        Self {
+            input: WordInput::Argv,
+            encoding: WordEncoding::Raw,
+            verdict: Verdict::Output {
+                accept: "1".to_string(),
+                reject: "0".to_string(),
+            },
+            timeout: None,
+            retries: 0,
+            retry_delay: Duration::ZERO,
+            jobs: 1,
+        }
+    }
+}
+
+pub struct Oracle {
+    path: String,
+    options: OracleOptions,
+}
+
+impl Oracle {
+    pub fn new(path: &str, options: OracleOptions) -> Self {
+        Self {
+            path: path.to_owned(),
+            options,
+        }
+    }
+
+    pub fn query(&self, word: &str) -> Result<bool> {
+        let mut attempt = 0;
+        loop {
+            match self.query_once(word) {
+                Err(_) if attempt < self.options.retries => {
+                    attempt += 1;
+                    thread::sleep(self.options.retry_delay);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    pub fn query_batch(&self, words: &[String]) -> Result<Vec<bool>> {
+        if self.options.jobs <= 1 || words.len() <= 1 {
+            return words.iter().map(|word| self.query(word)).collect();
+        }
+
+        let next_word = AtomicUsize::new(0);
+        let failed = AtomicBool::new(false);
+        let results = Mutex::new(Vec::from_iter((0..words.len()).map(|_| None)));
+
+        thread::scope(|scope| {
+            for _ in 0..self.options.jobs.min(words.len()) {
+                scope.spawn(|| {
+                    while !failed.load(Ordering::Relaxed) {
+                        let index = next_word.fetch_add(1, Ordering::Relaxed);
+                        if index >= words.len() {
+                            break;
+                        }
+
+                        let result = self.query(&words[index]);
+                        if result.is_err() {
+                            failed.store(true, Ordering::Relaxed);
+                        }
+                        results.lock().unwrap()[index] = Some(result);
+                    }
+                });
+            }
+        });
+
+        // После ошибки оставшиеся слова не запрашиваются, а результатом становится ошибка
+        results.into_inner().unwrap().into_iter().flatten().collect()
+    }
+
+    fn query_once(&self, word: &str) -> Result<bool> {
+        let encoded_word = self.encode(word);
+
+        let word_file = match self.options.input {
+            WordInput::File => Some(self.write_word_file(&encoded_word)?),
+            _ => None,
+        };
+        let result = self.run(word, &encoded_word, word_file.as_ref());
+        if let Some(path) = word_file {
+            let _ = fs::remove_file(path);
+        }
+
+        result
+    }
+
+    fn run(&self, word: &str, encoded_word: &str, word_file: Option<&PathBuf>) -> Result<bool> {
+        let to_error = |source| Error::Spawn {
+            word: word.to_owned(),
+            source,
+        };
+
+        let mut command = Command::new(&self.path);
+        match self.options.input {
+            WordInput::Argv => command.arg(encoded_word).stdin(Stdio::null()),
+            WordInput::Stdin => command.stdin(Stdio::piped()),
+            WordInput::File => command.arg(word_file.unwrap()).stdin(Stdio::null()),
+        };
+        let mut child = command.stdout(Stdio::piped()).spawn().map_err(to_error)?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            // Оракул может завершиться, не дочитав слово
+            let _ = writeln!(stdin, "{encoded_word}");
+        }
+
+        // Вывод читается отдельно, чтобы оракул не заблокировался на полном канале
+        let mut stdout = child.stdout.take().unwrap();
+        let reader = thread::spawn(move || {
+            let mut output = Vec::new();
+            stdout.read_to_end(&mut output).map(|_| output)
+        });
+
+        let status = self.wait(&mut child, word)?;
+        let output = reader.join().unwrap().map_err(to_error)?;
+        let output = String::from_utf8_lossy(&output);
+
+        match &self.options.verdict {
+            Verdict::ExitCode => match status.code() {
+                Some(0) => Ok(true),
+                Some(1) => Ok(false),
+                code => Err(Error::ExitCode {
+                    word: word.to_owned(),
+                    code,
+                }),
+            },
+            Verdict::Output { accept, reject } => {
+                if !status.success() {
+                    return Err(Error::ExitCode {
+                        word: word.to_owned(),
+                        code: status.code(),
+                    });
+                }
+
+                match output.trim() {
+                    verdict if verdict == accept => Ok(true),
+                    verdict if verdict == reject => Ok(false),
+                    _ => Err(Error::InvalidOutput {
+                        word: word.to_owned(),
+                        output: output.into_owned(),
+                    }),
+                }
+            }
+        }
+    }
+
+    fn encode(&self, word: &str) -> String {
+        match self.options.encoding {
+            WordEncoding::Raw => word.to_owned(),
+            WordEncoding::Hex => word.bytes().map(|byte| format!("{byte:02x}")).collect(),
+            WordEncoding::Symbols => {
+                let symbols: Vec<String> = word.chars().map(String::from).collect();
+                symbols.join(" ")
+            }
+        }
+    }
+
+    fn write_word_file(&self, encoded_word: &str) -> Result<PathBuf> {
+        let counter = WORD_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!(
+            "lab3_solo-{}-{counter}.txt",
+            std::process::id()
+        ));
+
+        fs::write(&path, encoded_word).map_err(|source| Error::Io {
+            path: path.display().to_string(),
+            source,
+        })?;
+
+        Ok(path)
+    }
+
+    fn wait(&self, child: &mut Child, word: &str) -> Result<std::process::ExitStatus> {
+        let to_error = |source| Error::Spawn {
+            word: word.to_owned(),
+            source,
+        };
+
+        let Some(timeout) = self.options.timeout else {
+            return child.wait().map_err(to_error);
+        };
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(status) = child.try_wait().map_err(to_error)? {
+                return Ok(status);
+            }
+
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(Error::Timeout {
+                    word: word.to_owned(),
+                    timeout,
+                });
+            }
+
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+    use std::sync::OnceLock;
+
+    use super::*;
+
+    // Принимает слова, содержащие `a`
+    const CONTAINS_A: &str = r#"case "$1" in *a*) echo 1 ;; *) echo 0 ;; esac"#;
+
+    // Все скрипты создаются до первого запуска: запись исполняемого файла
+    // одновременно с запуском другого процесса может дать ETXTBSY
+    fn get_script(name: &str) -> String {
+        static DIRECTORY: OnceLock<PathBuf> = OnceLock::new();
+        let directory = DIRECTORY.get_or_init(|| {
+            let directory =
+                std::env::temp_dir().join(format!("lab3_solo-oracle-tests-{}", std::process::id()));
+            fs::create_dir_all(&directory).unwrap();
+            let counter = directory.join("counter");
+            let scripts = [
+                ("argv", CONTAINS_A.to_string()),
+                (
+                    "stdin",
+                    format!("read word; set -- \"$word\"; {CONTAINS_A}"),
+                ),
+                ("file", format!("set -- \"$(cat \"$1\")\"; {CONTAINS_A}")),
+                ("hex", r#"[ "$1" = 61c3a9 ] && echo 1 || echo 0"#.into()),
+                ("symbols", r#"[ "$1" = "a b" ] && echo 1 || echo 0"#.into()),
+                (
+                    "words",
+                    r#"case "$1" in a) echo yes ;; b) echo no ;; *) echo maybe ;; esac"#.into(),
+                ),
+                (
+                    "exit",
+                    r#"case "$1" in a) exit 0 ;; b) exit 1 ;; *) exit 2 ;; esac"#.into(),
+                ),
+                ("sleep", "sleep 5; echo 1".into()),
+                (
+                    "flaky",
+                    format!(
+                        "n=$(cat {0} 2>/dev/null || echo 0); echo $((n + 1)) > {0}; \
+                         [ \"$n\" -ge 2 ] && echo 1 || exit 3",
+                        counter.display()
+                    ),
+                ),
+            ];
+            for (name, body) in scripts {
+                let path = directory.join(name);
+                fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+                fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+            }
+            directory
+        });
+        directory.join(name).display().to_string()
+    }
+
+    fn get_oracle(name: &str, options: OracleOptions) -> Oracle {
+        Oracle::new(&get_script(name), options)
+    }
+
+    fn query_words(oracle: &Oracle, words: &[&str]) -> Vec<bool> {
+        words
+            .iter()
+            .map(|word| oracle.query(word).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn passes_word_by_argv_stdin_and_file() {
+        for (name, input) in [
+            ("argv", WordInput::Argv),
+            ("stdin", WordInput::Stdin),
+            ("file", WordInput::File),
+        ] {
+            let oracle = get_oracle(
+                name,
+                OracleOptions {
+                    input,
+                    ..Default::default()
+                },
+            );
+            assert_eq!(
+                query_words(&oracle, &["", "a", "b", "ba"]),
+                [false, true, false, true]
+            );
+        }
+    }
+
+    #[test]
+    fn encodes_words() {
+        let hex = get_oracle(
+            "hex",
+            OracleOptions {
+                encoding: WordEncoding::Hex,
+                ..Default::default()
+            },
+        );
+        assert_eq!(query_words(&hex, &["aé", "ae"]), [true, false]);
+
+        let symbols = get_oracle(
+            "symbols",
+            OracleOptions {
+                encoding: WordEncoding::Symbols,
+                ..Default::default()
+            },
+        );
+        assert_eq!(query_words(&symbols, &["ab", "a"]), [true, false]);
+    }
+
+    #[test]
+    fn reads_verdict_from_output() {
+        let oracle = get_oracle(
+            "words",
+            OracleOptions {
+                verdict: Verdict::Output {
+                    accept: "yes".to_string(),
+                    reject: "no".to_string(),
+                },
+                ..Default::default()
+            },
+        );
+        assert_eq!(query_words(&oracle, &["a", "b"]), [true, false]);
+        assert!(matches!(
+            oracle.query("c"),
+            Err(Error::InvalidOutput { word, output }) if word == "c" && output == "maybe\n"
+        ));
+    }
+
+    #[test]
+    fn reads_verdict_from_exit_code() {
+        let oracle = get_oracle(
+            "exit",
+            OracleOptions {
+                verdict: Verdict::ExitCode,
+                ..Default::default()
+            },
+        );
+        assert_eq!(query_words(&oracle, &["a", "b"]), [true, false]);
+        assert!(matches!(
+            oracle.query("c"),
+            Err(Error::ExitCode { code: Some(2), .. })
+        ));
+    }
+
+    #[test]
+    fn reports_oracle_failures() {
+        // Оракул должен завершиться успешно и напечатать вердикт
+        let oracle = get_oracle("exit", OracleOptions::default());
+        assert!(matches!(
+            oracle.query("a"),
+            Err(Error::InvalidOutput { .. })
+        ));
+        assert!(matches!(
+            oracle.query("b"),
+            Err(Error::ExitCode { code: Some(1), .. })
+        ));
+    }
+
+    #[test]
+    fn kills_oracle_after_timeout() {
+        let oracle = get_oracle(
+            "sleep",
+            OracleOptions {
+                timeout: Some(Duration::from_millis(100)),
+                ..Default::default()
+            },
+        );
+        let start = Instant::now();
+        assert!(matches!(oracle.query("a"), Err(Error::Timeout { .. })));
+        assert!(start.elapsed() < Duration::from_secs(3));
+    }
+
+    #[test]
+    fn retries_failed_queries() {
+        let counter = Path::new(&get_script("counter")).to_owned();
+        let get_flaky_oracle = |retries| {
+            let _ = fs::remove_file(&counter);
+            get_oracle(
+                "flaky",
+                OracleOptions {
+                    retries,
+                    ..Default::default()
+                },
+            )
+        };
+
+        // Скрипт отвечает только с третьего запуска
+        assert!(matches!(
+            get_flaky_oracle(1).query("a"),
+            Err(Error::ExitCode { code: Some(3), .. })
+        ));
+        assert!(get_flaky_oracle(2).query("a").unwrap());
+    }
+
+    #[test]
+    fn reports_missing_oracle() {
+        let oracle = Oracle::new("/nonexistent/oracle", OracleOptions::default());
+        assert!(matches!(oracle.query("a"), Err(Error::Spawn { .. })));
+    }
+
+    #[test]
+    fn answers_batches_in_order() {
+        let words =
+            Vec::from_iter((0..20).map(|index| "b".repeat(index % 3) + ["", "a"][index % 2]));
+        let expected = Vec::from_iter(words.iter().map(|word| word.contains('a')));
+
+        for jobs in [1, 4] {
+            let oracle = get_oracle(
+                "argv",
+                OracleOptions {
+                    jobs,
+                    ..Default::default()
+                },
+            );
+            assert_eq!(oracle.query_batch(&words).unwrap(), expected);
+        }
+
+        let oracle = get_oracle(
+            "exit",
+            OracleOptions {
+                verdict: Verdict::ExitCode,
+                jobs: 4,
+                ..Default::default()
+            },
+        );
+        let words = ["a", "b", "c", "a"].map(String::from);
+        assert!(matches!(
+            oracle.query_batch(&words),
+            Err(Error::ExitCode { .. })
+        ));
+    }
+}
@@ -0,0 +1,54 @@
+use crate::automata::{get_automata_impl, Automata, AutomataImpl};
+use crate::error::{Error, Result};
+use crate::mat::{EquivalenceCheckResult, Mat};
+
+// Точный учитель: целевой язык задан автоматом, поэтому запросы принадлежности
+// выполняются на нём, а контрпример ищется обходом произведения автоматов.
+pub struct ReferenceMat {
+    alphabet: String,
+    dfa: AutomataImpl,
+}
+
+impl Mat for ReferenceMat {
+    fn check_membership(&self, word: &str) -> Result<bool> {
+        Ok(self.dfa.check_membership(word))
+    }
+
+    fn check_equivalence(&self, automata: &dyn Automata) -> Result<EquivalenceCheckResult> {
+        let hypothesis = get_automata_impl(automata);
+
+        Ok(match self.dfa.find_distinguishing_word(hypothesis, &self.alphabet) {
+            Some(word) => EquivalenceCheckResult::Counterexample(word),
+            None => EquivalenceCheckResult::Ok,
+        })
+    }
+
+    fn get_alphabet(&self) -> String {
+        self.alphabet.to_string()
+    }
+}
+
+impl ReferenceMat {
+    pub fn new(alphabet: &str, automata: &AutomataImpl) -> Self {
+        let dfa = automata.determinize();
+
+        Self {
+            alphabet: alphabet.to_owned(),
+            dfa: get_automata_impl(dfa.as_ref()).clone(),
+        }
+    }
+
+    pub fn from_regex(alphabet: &str, regex: &str) -> Result<Self> {
+        let automata = crate::regex::parse(regex).map_err(Error::Parse)?;
+        Ok(Self::new(alphabet, &automata))
+    }
+
+    pub fn from_file(alphabet: &str, path: &str) -> Result<Self> {
+        let text = std::fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+        let automata = AutomataImpl::parse(&text).map_err(Error::Parse)?;
+        Ok(Self::new(alphabet, &automata))
+    }
+}
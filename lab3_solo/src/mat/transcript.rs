@@ -0,0 +1,320 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::automata::{get_automata_impl, Automata, AutomataImpl};
+use crate::error::{Error, Result};
+use crate::mat::{EquivalenceCheckResult, Mat};
+
+// Протокол хранится построчно в JSON, время указывается в миллисекундах от
+// начала эпохи Unix
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "query", rename_all = "snake_case")]
+pub enum TranscriptEntry {
+    Start {
+        alphabet: String,
+        timestamp: u64,
+    },
+    Membership {
+        word: String,
+        answer: bool,
+        timestamp: u64,
+    },
+    Equivalence {
+        hypothesis: AutomataImpl,
+        counterexample: Option<String>,
+        timestamp: u64,
+    },
+}
+
+fn get_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+// Записывает все запросы к учителю и ответы на них
+pub struct RecordingMat<'a> {
+    mat: &'a dyn Mat,
+    path: String,
+    writer: RefCell<BufWriter<File>>,
+}
+
+impl<'a> Mat for RecordingMat<'a> {
+    fn check_membership(&self, word: &str) -> Result<bool> {
+        let answer = self.mat.check_membership(word)?;
+        self.record(&TranscriptEntry::Membership {
+            word: word.to_owned(),
+            answer,
+            timestamp: get_timestamp(),
+        })?;
+        Ok(answer)
+    }
+
+    fn check_membership_batch(&self, words: &[String]) -> Result<Vec<bool>> {
+        let answers = self.mat.check_membership_batch(words)?;
+        for (word, answer) in words.iter().zip(&answers) {
+            self.record(&TranscriptEntry::Membership {
+                word: word.to_owned(),
+                answer: *answer,
+                timestamp: get_timestamp(),
+            })?;
+        }
+        Ok(answers)
+    }
+
+    fn check_equivalence(&self, automata: &dyn Automata) -> Result<EquivalenceCheckResult> {
+        self.check_equivalence_through(automata, self)
+    }
+
+    // Запросы тестирования записываются как обычные запросы принадлежности
+    fn check_equivalence_through(
+        &self,
+        automata: &dyn Automata,
+        mat: &dyn Mat,
+    ) -> Result<EquivalenceCheckResult> {
+        let result = self.mat.check_equivalence_through(automata, mat)?;
+        let counterexample = match &result {
+            EquivalenceCheckResult::Ok => None,
+            EquivalenceCheckResult::Counterexample(word) => Some(word.to_owned()),
+        };
+        self.record(&TranscriptEntry::Equivalence {
+            hypothesis: get_automata_impl(automata).clone(),
+            counterexample,
+            timestamp: get_timestamp(),
+        })?;
+        Ok(result)
+    }
+
+    fn get_alphabet(&self) -> String {
+        self.mat.get_alphabet()
+    }
+
+    fn get_equivalence_position(&self) -> Option<serde_json::Value> {
+        self.mat.get_equivalence_position()
+    }
+
+    fn set_equivalence_position(&self, position: serde_json::Value) -> Result<()> {
+        self.mat.set_equivalence_position(position)
+    }
+}
+
+impl<'a> RecordingMat<'a> {
+    pub fn new(mat: &'a dyn Mat, path: &str) -> Result<Self> {
+        let file = File::create(path).map_err(|source| Error::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+
+        let recording_mat = Self {
+            mat,
+            path: path.to_owned(),
+            writer: RefCell::new(BufWriter::new(file)),
+        };
+        recording_mat.record(&TranscriptEntry::Start {
+            alphabet: mat.get_alphabet(),
+            timestamp: get_timestamp(),
+        })?;
+
+        Ok(recording_mat)
+    }
+
+    fn record(&self, entry: &TranscriptEntry) -> Result<()> {
+        let mut writer = self.writer.borrow_mut();
+        let line = serde_json::to_string(entry).unwrap();
+
+        // Протокол сбрасывается на диск сразу, чтобы пережить аварийное завершение
+        writeln!(writer, "{line}")
+            .and_then(|_| writer.flush())
+            .map_err(|source| Error::Io {
+                path: self.path.to_owned(),
+                source,
+            })
+    }
+}
+
+// Отвечает только по записанному протоколу
+pub struct ReplayMat {
+    alphabet: String,
+    memberships: HashMap<String, bool>,
+    equivalences: Vec<(AutomataImpl, Option<String>)>,
+    next_equivalence: Cell<usize>,
+}
+
+impl Mat for ReplayMat {
+    fn check_membership(&self, word: &str) -> Result<bool> {
+        self.memberships
+            .get(word)
+            .copied()
+            .ok_or_else(|| Error::UnseenQuery(format!("membership of word {word:?}")))
+    }
+
+    fn check_equivalence(&self, automata: &dyn Automata) -> Result<EquivalenceCheckResult> {
+        let index = self.next_equivalence.get();
+        let Some((hypothesis, counterexample)) = self.equivalences.get(index) else {
+            return Err(Error::UnseenQuery(format!(
+                "equivalence query #{}, the transcript has only {}",
+                index + 1,
+                self.equivalences.len()
+            )));
+        };
+
+        // Нумерация состояний может отличаться, поэтому гипотезы сравниваются по языку
+        let distinguishing_word =
+            hypothesis.find_distinguishing_word(get_automata_impl(automata), &self.alphabet);
+        if let Some(word) = distinguishing_word {
+            return Err(Error::UnseenQuery(format!(
+                "equivalence query #{} with a hypothesis that differs from the recorded one on word {word:?}",
+                index + 1
+            )));
+        }
+        self.next_equivalence.set(index + 1);
+
+        Ok(match counterexample {
+            Some(word) => EquivalenceCheckResult::Counterexample(word.to_owned()),
+            None => EquivalenceCheckResult::Ok,
+        })
+    }
+
+    fn get_alphabet(&self) -> String {
+        self.alphabet.to_string()
+    }
+
+    // Номер следующего записанного запроса эквивалентности
+    fn get_equivalence_position(&self) -> Option<serde_json::Value> {
+        Some(serde_json::Value::from(self.next_equivalence.get()))
+    }
+
+    fn set_equivalence_position(&self, position: serde_json::Value) -> Result<()> {
+        let index = serde_json::from_value(position).map_err(|error| {
+            Error::Checkpoint(format!("invalid transcript position: {error}"))
+        })?;
+        self.next_equivalence.set(index);
+        Ok(())
+    }
+}
+
+impl ReplayMat {
+    pub fn from_file(path: &str) -> Result<Self> {
+        let to_error = |source| Error::Io {
+            path: path.to_owned(),
+            source,
+        };
+        let file = File::open(path).map_err(to_error)?;
+
+        let mut alphabet = None;
+        let mut memberships = HashMap::new();
+        let mut equivalences = Vec::new();
+
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(to_error)?;
+            let entry: TranscriptEntry = serde_json::from_str(&line)
+                .map_err(|error| Error::Parse(format!("{path}:{}: {error}", number + 1)))?;
+
+            match entry {
+                TranscriptEntry::Start { alphabet: a, .. } => alphabet = Some(a),
+                TranscriptEntry::Membership { word, answer, .. } => {
+                    memberships.insert(word, answer);
+                }
+                TranscriptEntry::Equivalence {
+                    hypothesis,
+                    counterexample,
+                    ..
+                } => equivalences.push((hypothesis, counterexample)),
+            }
+        }
+
+        let alphabet =
+            alphabet.ok_or_else(|| Error::Parse(format!("{path}: transcript has no start entry")))?;
+
+        Ok(Self {
+            alphabet,
+            memberships,
+            equivalences,
+            next_equivalence: Cell::new(0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::learner::{learn, LearnerConfig};
+    use crate::mat::reference::ReferenceMat;
+
+    fn get_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!(
+            "lab3_solo-transcript-{}-{name}.jsonl",
+            std::process::id()
+        ));
+        path.display().to_string()
+    }
+
+    #[test]
+    fn replays_recorded_run() {
+        let path = get_path("run");
+        let mat = ReferenceMat::from_regex("ab", "(a|b)*a(a|b)").unwrap();
+        let recorded = learn(
+            &LearnerConfig::default(),
+            &RecordingMat::new(&mat, &path).unwrap(),
+        )
+        .unwrap();
+
+        let replay_mat = ReplayMat::from_file(&path).unwrap();
+        let replayed = learn(&LearnerConfig::default(), &replay_mat).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(replayed.hypothesis, recorded.hypothesis);
+        assert_eq!(replayed.statistics.rounds, recorded.statistics.rounds);
+        assert_eq!(
+            replayed.statistics.membership_queries,
+            recorded.statistics.membership_queries
+        );
+        assert_eq!(replay_mat.get_equivalence_position(), Some(2.into()));
+    }
+
+    #[test]
+    fn rejects_queries_missing_from_transcript() {
+        let path = get_path("missing");
+        let mat = ReferenceMat::from_regex("ab", "a*").unwrap();
+        let recording_mat = RecordingMat::new(&mat, &path).unwrap();
+        recording_mat.check_membership("aa").unwrap();
+        let hypothesis = crate::regex::parse("a*").unwrap().determinize();
+        recording_mat
+            .check_equivalence(hypothesis.as_ref())
+            .unwrap();
+        drop(recording_mat);
+
+        let replay_mat = ReplayMat::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(replay_mat.get_alphabet(), "ab");
+        assert!(replay_mat.check_membership("aa").unwrap());
+        assert!(matches!(
+            replay_mat.check_membership("b"),
+            Err(Error::UnseenQuery(_))
+        ));
+
+        // Гипотеза другого языка не совпадает с записанной
+        let other = crate::regex::parse("b*").unwrap().determinize();
+        assert!(matches!(
+            replay_mat.check_equivalence(other.as_ref()),
+            Err(Error::UnseenQuery(_))
+        ));
+        assert!(matches!(
+            replay_mat.check_equivalence(hypothesis.as_ref()),
+            Ok(EquivalenceCheckResult::Ok)
+        ));
+        assert!(matches!(
+            replay_mat.check_equivalence(hypothesis.as_ref()),
+            Err(Error::UnseenQuery(_))
+        ));
+
+        // После восстановления положения запрос отвечается заново
+        replay_mat.set_equivalence_position(0.into()).unwrap();
+        assert!(replay_mat.check_equivalence(hypothesis.as_ref()).is_ok());
+    }
+}
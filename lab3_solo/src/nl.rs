@@ -1,210 +1,264 @@
-#![allow(dead_code)]
-
-mod extended_table;
-mod main_table;
+mod bitset;
+pub mod counterexample;
+mod observation_table;
+pub mod render;
+pub mod seed;
 
 use std::collections::HashMap;
 
-use crate::automata::{Automata, AutomataImpl, EPSILON, START};
-use crate::mat::{EquivalenceCheckResult, Mat};
-use crate::nl::extended_table::ExtendedTable;
-use crate::nl::main_table::{CoverageMode, MainTable};
+use serde::{Deserialize, Serialize};
 
-// TODO: оптимизировать итерации в check_consistency
-// TODO: использовать BTreeSet заместо HashSet?
+use crate::automata::{compare_shortlex, get_automata_impl, Automata, AutomataImpl, EPSILON};
+use crate::error::{Error, Result};
+use crate::events::{Event, EventLog, Verbosity};
+use crate::learner::{Learner, RoundResult, TableLearner, TableStatistics};
+use crate::mat::{EquivalenceCheckResult, Mat};
+use crate::nl::counterexample::CounterexampleStrategy;
+use crate::nl::render::TableView;
+use crate::nl::seed::Seed;
+use crate::nl::observation_table::{
+    ClosednessCheckResult, ConsistencyCheckResult, ObservationTable, ObservationTableState,
+    EPSILON_SUFFIX_ID,
+};
+
+// Остаточный автомат: состояние i соответствует простой строке prime_prefixes[i],
+// язык состояния -- остаток языка по этой строке
+pub struct Rfsa {
+    pub automata: AutomataImpl,
+    pub prime_prefixes: Vec<String>,
+}
 
-pub trait Nl {
-    fn get_dfa(&mut self) -> Box<dyn Automata>;
+// Состояние NL* между раундами, сохраняемое в контрольной точке
+#[derive(Serialize, Deserialize)]
+struct NlState {
+    table: ObservationTableState,
+    closedness_repairs: usize,
+    consistency_repairs: usize,
 }
 
 pub struct NlImpl<'a> {
     mat: &'a dyn Mat,
-    main_table: MainTable<'a>,
-    extended_table: ExtendedTable<'a>,
+    table: ObservationTable<'a>,
+    counterexample_strategy: CounterexampleStrategy,
+    shorten_counterexamples: bool,
+    event_log: EventLog,
+    round: usize,
+    closedness_repairs: usize,
+    consistency_repairs: usize,
 }
 
-impl<'a> Nl for NlImpl<'a> {
-    fn get_dfa(&mut self) -> Box<dyn Automata> {
+impl<'a> Learner for NlImpl<'a> {
+    fn run_round(&mut self) -> Result<RoundResult> {
+        self.round += 1;
         loop {
-            dbg!(&self.main_table.prefixes);
-            dbg!(&self.main_table.basic_prefixes);
-            dbg!(&self.main_table.suffixes);
-            dbg!(&self.extended_table.prefixes);
-            dbg!(&self.extended_table.suffixes);
-
-            if let CompletenessCheckResult::UncoveredPrefix(prefix) = self.check_completeness() {
-                println!("fix completeness");
-                self.insert_prefix(&prefix);
+            if self.event_log.is_enabled(Verbosity::Debug) {
+                self.event_log.emit(&Event::Table {
+                    round: self.round,
+                    table: &self.table.get_view(),
+                });
+            }
+
+            if let ClosednessCheckResult::UnclosedPrefix(prefix) = self.table.check_closedness() {
+                self.event_log.emit(&Event::FixCompleteness {
+                    round: self.round,
+                    prefix: &prefix,
+                    table_size: self.table.get_size(),
+                });
+                self.insert_prefix(&prefix)?;
+                self.closedness_repairs += 1;
                 continue;
             }
 
-            if let ConsistencyCheckResult::DistinguishingSuffix(suffix) = self.check_consistency() {
-                println!("fix consistency");
-                self.insert_suffix(&suffix);
+            if let ConsistencyCheckResult::DistinguishingSuffix(suffix) =
+                self.table.check_consistency()
+            {
+                self.event_log.emit(&Event::FixConsistency {
+                    round: self.round,
+                    suffix: &suffix,
+                    table_size: self.table.get_size(),
+                });
+                self.insert_suffix(&suffix)?;
+                self.consistency_repairs += 1;
                 continue;
             }
 
-            let nfa = self.build_nfa();
-            let dfa = nfa.determinize();
+            let rfsa = self.build_rfsa();
+            if self.event_log.is_enabled(Verbosity::Debug) {
+                self.emit_rfsa(&rfsa);
+            }
+            let dfa = rfsa.automata.determinize();
 
             if let EquivalenceCheckResult::Counterexample(word) =
-                self.mat.check_equivalence(dfa.as_ref())
+                self.mat.check_equivalence(dfa.as_ref())?
             {
-                println!("counterexample");
-                self.insert_prefix_recursive(&word);
-                continue;
+                self.event_log.emit(&Event::Counterexample {
+                    round: self.round,
+                    word: &word,
+                    table_size: self.table.get_size(),
+                });
+                let hypothesis = get_automata_impl(dfa.as_ref());
+                let word = if self.shorten_counterexamples {
+                    self.shorten_counterexample(&word, hypothesis)?
+                } else {
+                    word
+                };
+                self.process_counterexample(&word, hypothesis)?;
+                break Ok(RoundResult::Counterexample(word));
             }
 
-            break dfa;
+            break Ok(RoundResult::Finished(dfa));
         }
     }
-}
 
-enum CompletenessCheckResult {
-    Ok,
-    UncoveredPrefix(String),
-}
+    fn as_table_learner(&self) -> Option<&dyn TableLearner> {
+        Some(self)
+    }
 
-enum ConsistencyCheckResult {
-    Ok,
-    DistinguishingSuffix(String),
+    fn get_state(&self) -> Option<serde_json::Value> {
+        let state = NlState {
+            table: self.table.get_state(),
+            closedness_repairs: self.closedness_repairs,
+            consistency_repairs: self.consistency_repairs,
+        };
+        Some(serde_json::to_value(state).unwrap())
+    }
 }
 
-impl<'a> NlImpl<'a> {
-    pub fn new(mat: &'a dyn Mat) -> Self {
-        Self {
-            mat,
-            main_table: MainTable::new(mat),
-            extended_table: ExtendedTable::new(mat),
+impl<'a> TableLearner for NlImpl<'a> {
+    fn get_table_statistics(&self) -> TableStatistics {
+        TableStatistics {
+            closedness_repairs: self.closedness_repairs,
+            consistency_repairs: self.consistency_repairs,
+            size: self.table.get_size(),
         }
     }
 
-    fn insert_prefix_recursive(&mut self, prefix: &str) {
-        for i in 1..=prefix.len() {
-            let word = &prefix[0..i];
-            self.insert_prefix(word);
-        }
+    fn get_table_view(&self) -> Option<TableView> {
+        Some(self.table.get_view())
+    }
+
+    fn get_rfsa(&self) -> Option<Rfsa> {
+        Some(self.build_rfsa())
     }
+}
 
-    fn insert_prefix(&mut self, prefix: &str) {
-        self.main_table.insert_prefix(prefix);
-        self.extended_table.insert_prefix(prefix);
+impl<'a> NlImpl<'a> {
+    pub fn new(
+        mat: &'a dyn Mat,
+        counterexample_strategy: CounterexampleStrategy,
+        shorten_counterexamples: bool,
+        event_log: EventLog,
+        seed: &Seed,
+    ) -> Result<Self> {
+        Ok(Self {
+            mat,
+            table: ObservationTable::new(mat, seed)?,
+            counterexample_strategy,
+            shorten_counterexamples,
+            event_log,
+            round: 0,
+            closedness_repairs: 0,
+            consistency_repairs: 0,
+        })
     }
 
-    fn insert_suffix(&mut self, suffix: &str) {
-        self.main_table.insert_suffix(suffix);
-        self.extended_table.insert_suffix(suffix);
+    // Продолжает обучение после round завершённых раундов, не обращаясь к учителю
+    pub fn from_state(
+        mat: &'a dyn Mat,
+        counterexample_strategy: CounterexampleStrategy,
+        shorten_counterexamples: bool,
+        event_log: EventLog,
+        state: serde_json::Value,
+        round: usize,
+        seed: &Seed,
+    ) -> Result<Self> {
+        let state: NlState = serde_json::from_value(state)
+            .map_err(|error| Error::Checkpoint(format!("invalid NL* state: {error}")))?;
+
+        Ok(Self {
+            mat,
+            table: ObservationTable::from_state(mat, state.table, seed.answers.clone()),
+            counterexample_strategy,
+            shorten_counterexamples,
+            event_log,
+            round,
+            closedness_repairs: state.closedness_repairs,
+            consistency_repairs: state.consistency_repairs,
+        })
     }
 
-    fn check_completeness(&self) -> CompletenessCheckResult {
-        for prefix in &self.extended_table.prefixes {
-            let membership_suffixes = self
-                .extended_table
-                .prefix_to_membership_suffixes
-                .get(prefix)
-                .unwrap();
-            if !self
-                .main_table
-                .is_covered(prefix, membership_suffixes, CoverageMode::Inclusive)
-            { return CompletenessCheckResult::UncoveredPrefix(prefix.to_owned());
+    fn emit_rfsa(&self, rfsa: &Rfsa) {
+        for (from, row) in rfsa.automata.transitions.iter().enumerate() {
+            for (to, labels) in row.iter().enumerate() {
+                for label in labels {
+                    self.event_log.emit(&Event::AddTransition {
+                        round: self.round,
+                        from,
+                        to,
+                        letter: label.chars().next().unwrap(),
+                    });
+                }
             }
         }
+        self.event_log.emit(&Event::Hypothesis {
+            round: self.round,
+            automata: &rfsa.automata,
+        });
+    }
 
-        CompletenessCheckResult::Ok
+    fn insert_prefix(&mut self, prefix: &str) -> Result<()> {
+        self.table.insert_prefix(prefix)
     }
 
-    fn check_consistency(&self) -> ConsistencyCheckResult {
-        for (prefix_1, membership_suffixes_1) in &self.main_table.prefix_to_membership_suffixes {
-            for (prefix_2, mebership_suffixes_2) in &self.main_table.prefix_to_membership_suffixes {
-                if !membership_suffixes_1.is_subset(mebership_suffixes_2) {
-                    continue;
-                }
+    fn insert_suffix(&mut self, suffix: &str) -> Result<()> {
+        self.table.insert_suffix(suffix)
+    }
 
-                for letter in self.mat.get_alphabet().chars() {
-                    let new_prefix_1 = format!("{prefix_1}{letter}");
-                    let new_prefix_2 = format!("{prefix_2}{letter}");
-
-                    let new_membership_suffixes_1 = self
-                        .extended_table
-                        .prefix_to_membership_suffixes
-                        .get(&new_prefix_1)
-                        .unwrap();
-                    let new_membership_suffixes_2 = self
-                        .extended_table
-                        .prefix_to_membership_suffixes
-                        .get(&new_prefix_2)
-                        .unwrap();
-
-                    if let Some(suffix) = new_membership_suffixes_1
-                        .difference(new_membership_suffixes_2)
-                        .next()
-                    {
-                        let distinguishing_suffix = format!("{letter}{suffix}");
-                        return ConsistencyCheckResult::DistinguishingSuffix(distinguishing_suffix);
-                    }
-                }
-            }
-        }
+    fn build_rfsa(&self) -> Rfsa {
+        let prime_prefix_ids = self.get_sorted_prime_prefixes();
+        let id_to_index: HashMap<usize, usize> = prime_prefix_ids
+            .iter()
+            .enumerate()
+            .map(|(index, prefix_id)| (*prefix_id, index))
+            .collect();
 
-        ConsistencyCheckResult::Ok
-    }
+        let mut automata = AutomataImpl::new(prime_prefix_ids.len());
+        automata.start_states = vec![false; prime_prefix_ids.len()];
 
-    fn build_nfa(&self) -> Box<dyn Automata> {
-        let mut automata = AutomataImpl::new(self.main_table.basic_prefixes.len() + 1);
-        let prefix_to_index = self.enumerate_basic_prefixes();
-
-        let epsilon_absorbed_prefixes = self.main_table.get_absorbed_basic_prefixes(
-            self.main_table
-                .prefix_to_membership_suffixes
-                .get(EPSILON)
-                .unwrap(),
-        );
-        for prefix in &epsilon_absorbed_prefixes {
-            let index = prefix_to_index.get(prefix).unwrap();
-            automata.transitions[START][*index].insert(EPSILON.to_owned());
+        let epsilon_row = &self.table.rows[self.table.get_prefix_id(EPSILON).unwrap()];
+        for prefix_id in self.table.get_absorbed_prime_prefixes(epsilon_row) {
+            automata.start_states[id_to_index[&prefix_id]] = true;
         }
 
-        for (prefix, index) in &prefix_to_index {
-            for letter in self.mat.get_alphabet().chars() {
-                let extension = format!("{prefix}{letter}");
-                let extension_absorbed_prefixes = self.main_table.get_absorbed_basic_prefixes(
-                    self.extended_table
-                        .prefix_to_membership_suffixes
-                        .get(&extension)
-                        .unwrap(),
-                );
-                for absorbed_prefix in &extension_absorbed_prefixes {
-                    let absorbed_prefix_index = prefix_to_index.get(absorbed_prefix).unwrap();
-                    automata.transitions[*index][*absorbed_prefix_index].insert(letter.to_string());
-                    println!("add transition {} to {} by {}", index, absorbed_prefix_index, letter);
+        for (index, prefix_id) in prime_prefix_ids.iter().enumerate() {
+            for (letter_index, letter) in self.mat.get_alphabet().chars().enumerate() {
+                let extension_id = self.table.get_successor(*prefix_id, letter_index);
+                let extension_row = &self.table.rows[extension_id];
+                for absorbed_prefix_id in self.table.get_absorbed_prime_prefixes(extension_row) {
+                    let absorbed_prefix_index = id_to_index[&absorbed_prefix_id];
+                    automata.transitions[index][absorbed_prefix_index].insert(letter.to_string());
                 }
             }
-        }
 
-        let epsilon_membership_prefixes = self
-            .main_table
-            .suffix_to_membership_prefixes
-            .get(EPSILON)
-            .unwrap()
-            .intersection(&self.main_table.basic_prefixes);
-        for prefix in epsilon_membership_prefixes {
-            let index = prefix_to_index.get(prefix).unwrap();
-            automata.finite_states[*index] = true;
+            automata.finite_states[index] =
+                self.table.rows[*prefix_id].contains(EPSILON_SUFFIX_ID);
         }
 
-        dbg!(&automata);
-
-        Box::new(automata)
-    }
-
-    fn enumerate_basic_prefixes(&self) -> HashMap<String, usize> {
-        let mut prefix_to_index = HashMap::new();
-
-        // Индекс 0 зарезервирован для стартового состояния
-        for (i, prefix) in self.main_table.basic_prefixes.iter().enumerate() {
-            prefix_to_index.insert(prefix.to_owned(), i + 1);
+        Rfsa {
+            automata,
+            prime_prefixes: prime_prefix_ids
+                .into_iter()
+                .map(|prefix_id| self.table.prefixes[prefix_id].to_owned())
+                .collect(),
         }
+    }
 
-        prefix_to_index
+    // Простые строки в shortlex-порядке, чтобы нумерация состояний не
+    // зависела от порядка обхода множества
+    fn get_sorted_prime_prefixes(&self) -> Vec<usize> {
+        let prefixes = &self.table.prefixes;
+        let mut prefix_ids = Vec::from_iter(self.table.prime_prefixes.iter());
+        prefix_ids.sort_by(|first, second| compare_shortlex(&prefixes[*first], &prefixes[*second]));
+        prefix_ids
     }
 }
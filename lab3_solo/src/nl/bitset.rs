@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+// Множество небольших неотрицательных чисел. Отсутствующие старшие слова
+// считаются нулевыми, поэтому множества разной длины сравниваются корректно.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl PartialEq for BitSet {
+    fn eq(&self, other: &Self) -> bool {
+        let length = self.words.len().max(other.words.len());
+        (0..length).all(|index| self.get_word(index) == other.get_word(index))
+    }
+}
+
+impl Eq for BitSet {}
+
+impl BitSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn get_word(&self, index: usize) -> u64 {
+        self.words.get(index).copied().unwrap_or(0)
+    }
+
+    pub fn insert(&mut self, value: usize) {
+        let index = value / WORD_BITS;
+        if index >= self.words.len() {
+            self.words.resize(index + 1, 0);
+        }
+        self.words[index] |= 1 << (value % WORD_BITS);
+    }
+
+    pub fn remove(&mut self, value: usize) {
+        if let Some(word) = self.words.get_mut(value / WORD_BITS) {
+            *word &= !(1 << (value % WORD_BITS));
+        }
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        self.get_word(value / WORD_BITS) & (1 << (value % WORD_BITS)) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|word| *word == 0)
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.words
+            .iter()
+            .enumerate()
+            .all(|(index, word)| word & !other.get_word(index) == 0)
+    }
+
+    pub fn union_with(&mut self, other: &Self) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (word, other_word) in self.words.iter_mut().zip(&other.words) {
+            *word |= other_word;
+        }
+    }
+
+    // Наименьший элемент, который есть в этом множестве, но не в другом
+    pub fn first_difference(&self, other: &Self) -> Option<usize> {
+        self.words.iter().enumerate().find_map(|(index, word)| {
+            let difference = word & !other.get_word(index);
+            (difference != 0).then(|| index * WORD_BITS + difference.trailing_zeros() as usize)
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(index, word)| {
+            (0..WORD_BITS)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| index * WORD_BITS + bit)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_values(values: &[usize]) -> BitSet {
+        let mut set = BitSet::new();
+        for value in values {
+            set.insert(*value);
+        }
+        set
+    }
+
+    #[test]
+    fn inserts_and_removes_values() {
+        let mut set = from_values(&[0, 63, 64, 200]);
+        assert!(set.contains(63) && set.contains(64) && set.contains(200));
+        assert!(!set.contains(1) && !set.contains(1000));
+        assert_eq!(Vec::from_iter(set.iter()), [0, 63, 64, 200]);
+
+        set.remove(64);
+        set.remove(1000);
+        assert!(!set.contains(64));
+        assert_eq!(Vec::from_iter(set.iter()), [0, 63, 200]);
+    }
+
+    #[test]
+    fn compares_sets_of_different_length() {
+        let mut set = from_values(&[3, 130]);
+        set.remove(130);
+        assert_eq!(set, from_values(&[3]));
+        assert_ne!(set, from_values(&[3, 4]));
+
+        set.remove(3);
+        assert!(set.is_empty());
+        assert_eq!(set, BitSet::new());
+    }
+
+    #[test]
+    fn checks_subsets() {
+        let small = from_values(&[1, 70]);
+        let large = from_values(&[1, 5, 70, 140]);
+        assert!(small.is_subset(&large));
+        assert!(!large.is_subset(&small));
+        assert!(BitSet::new().is_subset(&small));
+        assert!(!from_values(&[140]).is_subset(&small));
+    }
+
+    #[test]
+    fn unites_sets() {
+        let mut set = from_values(&[2]);
+        set.union_with(&from_values(&[2, 66, 190]));
+        assert_eq!(set, from_values(&[2, 66, 190]));
+    }
+
+    #[test]
+    fn finds_first_difference() {
+        let set = from_values(&[1, 70, 140]);
+        assert_eq!(set.first_difference(&from_values(&[1])), Some(70));
+        assert_eq!(set.first_difference(&from_values(&[1, 70])), Some(140));
+        assert_eq!(set.first_difference(&from_values(&[1, 70, 140, 200])), None);
+    }
+}
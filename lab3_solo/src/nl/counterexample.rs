@@ -0,0 +1,228 @@
+use std::collections::HashSet;
+
+use crate::automata::{Automata, AutomataImpl};
+use crate::error::Result;
+use crate::nl::NlImpl;
+
+#[derive(Clone, Copy)]
+pub enum CounterexampleStrategy {
+    // Все префиксы контрпримера добавляются в строки, как в L*
+    AllPrefixes,
+    // Все суффиксы контрпримера добавляются в столбцы, как в NL*
+    AllSuffixes,
+    // Бинарным поиском находится один различающий суффикс
+    RivestSchapire,
+    // Суффиксы остатка контрпримера после самого длинного его префикса из
+    // строк таблицы (метод Малера–Пнуэли с уточнением Шахбаза)
+    MalerPnueli,
+}
+
+impl<'a> NlImpl<'a> {
+    pub(super) fn process_counterexample(
+        &mut self,
+        counterexample: &str,
+        hypothesis: &AutomataImpl,
+    ) -> Result<()> {
+        match self.counterexample_strategy {
+            CounterexampleStrategy::AllPrefixes => self.insert_prefix_recursive(counterexample),
+            CounterexampleStrategy::AllSuffixes => self.insert_suffix_recursive(counterexample),
+            CounterexampleStrategy::RivestSchapire => {
+                match self.find_distinguishing_suffix(counterexample, hypothesis)? {
+                    Some(suffix) if !self.table.contains_suffix(&suffix) => {
+                        self.insert_suffix(&suffix)
+                    }
+                    // Гипотеза NL* не обязана быть согласована с таблицей, поэтому
+                    // различающего суффикса может не найтись
+                    _ => self.insert_suffix_recursive(counterexample),
+                }
+            }
+            CounterexampleStrategy::MalerPnueli => {
+                let split = get_splits(counterexample)
+                    .filter(|split| self.table.contains_upper_prefix(&counterexample[..*split]))
+                    .max()
+                    .unwrap();
+                self.insert_suffix_recursive(&counterexample[split..])
+            }
+        }
+    }
+
+    // Укорачивает контрпример запросами принадлежности: префиксы заменяются
+    // словами доступа к тем же состояниям гипотезы, затем удаляются подслова,
+    // пока слово остаётся контрпримером
+    pub(super) fn shorten_counterexample(
+        &self,
+        counterexample: &str,
+        hypothesis: &AutomataImpl,
+    ) -> Result<String> {
+        let access_words = hypothesis.get_access_words(&self.mat.get_alphabet());
+        let mut tried_words = HashSet::from([counterexample.to_owned()]);
+        let mut is_counterexample = |word: &str| -> Result<bool> {
+            if !tried_words.insert(word.to_owned()) {
+                return Ok(false);
+            }
+            Ok(self.mat.check_membership(word)? != hypothesis.check_membership(word))
+        };
+
+        let mut counterexample = counterexample.to_owned();
+
+        let splits: Vec<usize> = get_splits(&counterexample).collect();
+        for split in splits.into_iter().rev() {
+            let (prefix, suffix) = counterexample.split_at(split);
+            let Some(access_word) = hypothesis
+                .get_state(prefix)
+                .and_then(|state| access_words[state].to_owned())
+            else {
+                continue;
+            };
+
+            if access_word.len() < prefix.len() {
+                let word = format!("{access_word}{suffix}");
+                if is_counterexample(&word)? {
+                    counterexample = word;
+                    break;
+                }
+            }
+        }
+
+        let mut length = counterexample.chars().count();
+        while length > 0 {
+            let mut start = 0;
+            while start + length <= counterexample.chars().count() {
+                let word: String = counterexample
+                    .chars()
+                    .take(start)
+                    .chain(counterexample.chars().skip(start + length))
+                    .collect();
+
+                if is_counterexample(&word)? {
+                    counterexample = word;
+                } else {
+                    start += 1;
+                }
+            }
+            length -= 1;
+        }
+
+        Ok(counterexample)
+    }
+
+    // Разбиение по границам символов, а не байтов: алфавит может быть не ASCII
+    fn insert_prefix_recursive(&mut self, word: &str) -> Result<()> {
+        for split in get_splits(word).skip(1) {
+            self.insert_prefix(&word[..split])?;
+        }
+
+        Ok(())
+    }
+
+    fn insert_suffix_recursive(&mut self, word: &str) -> Result<()> {
+        for split in get_splits(word) {
+            self.insert_suffix(&word[split..])?;
+        }
+
+        Ok(())
+    }
+
+    // Ищет разбиение контрпримера u a v, при котором ответы на ⌊u⌋ a v и
+    // ⌊u a⌋ v различаются, где ⌊u⌋ -- слово доступа к состоянию гипотезы
+    fn find_distinguishing_suffix(
+        &self,
+        counterexample: &str,
+        hypothesis: &AutomataImpl,
+    ) -> Result<Option<String>> {
+        let splits: Vec<usize> = get_splits(counterexample).collect();
+        let access_words = hypothesis.get_access_words(&self.mat.get_alphabet());
+
+        let get_answer = |index: usize| {
+            let (prefix, suffix) = counterexample.split_at(splits[index]);
+            let access_word = hypothesis
+                .get_state(prefix)
+                .and_then(|state| access_words[state].to_owned())
+                .unwrap_or_else(|| prefix.to_owned());
+            self.mat.check_membership(&format!("{access_word}{suffix}"))
+        };
+
+        let mut low = 0;
+        let mut high = splits.len() - 1;
+        let low_answer = get_answer(low)?;
+        if low_answer == get_answer(high)? {
+            return Ok(None);
+        }
+
+        while high - low > 1 {
+            let middle = (low + high) / 2;
+            if get_answer(middle)? == low_answer {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+
+        Ok(Some(counterexample[splits[high]..].to_owned()))
+    }
+}
+
+// Границы всех разбиений слова на префикс и суффикс
+fn get_splits(word: &str) -> impl Iterator<Item = usize> + '_ {
+    word.char_indices()
+        .map(|(index, _)| index)
+        .chain(std::iter::once(word.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automata::get_automata_impl;
+    use crate::events::EventLog;
+    use crate::mat::reference::ReferenceMat;
+    use crate::mat::Mat;
+    use crate::nl::seed::Seed;
+
+    const TARGET: &str = "(a|b)*a(a|b)(a|b)";
+
+    fn shorten(hypothesis: &str, counterexample: &str) -> String {
+        let mat = ReferenceMat::from_regex("ab", TARGET).unwrap();
+        let learner = NlImpl::new(
+            &mat,
+            CounterexampleStrategy::AllSuffixes,
+            true,
+            EventLog::default(),
+            &Seed::default(),
+        )
+        .unwrap();
+        let hypothesis = crate::regex::parse(hypothesis).unwrap().determinize();
+        let hypothesis = get_automata_impl(hypothesis.as_ref()).minimize("ab");
+
+        assert_ne!(
+            mat.check_membership(counterexample).unwrap(),
+            hypothesis.check_membership(counterexample)
+        );
+        let word = learner
+            .shorten_counterexample(counterexample, &hypothesis)
+            .unwrap();
+        assert!(word.len() <= counterexample.len());
+        assert_ne!(
+            mat.check_membership(&word).unwrap(),
+            hypothesis.check_membership(&word),
+            "{word:?} is not a counterexample"
+        );
+        word
+    }
+
+    #[test]
+    fn shortens_counterexample_to_shortest_word() {
+        assert_eq!(shorten("∅", "bbbbbbabb"), "abb");
+        assert_eq!(shorten("(a|b)*", "ababbbb"), "");
+    }
+
+    #[test]
+    fn keeps_counterexample_after_shortening() {
+        for (hypothesis, counterexample) in [
+            ("(a|b)*a", "abbabbaa"),
+            ("(a|b)*a(a|b)", "bbaabbbbab"),
+            ("a*", "aaaaaaaaaab"),
+        ] {
+            shorten(hypothesis, counterexample);
+        }
+    }
+}
@@ -0,0 +1,377 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::automata::{compare_shortlex, EPSILON};
+use crate::error::Result;
+use crate::events::TableSize;
+use crate::mat::Mat;
+use crate::nl::bitset::BitSet;
+use crate::nl::render::{TableRow, TableView};
+use crate::nl::seed::Seed;
+
+// Таблица наблюдений NL*: верхняя часть -- строки префиксов S, нижняя -- их
+// продолжения S·Σ, не попавшие в S, столбцы -- суффиксы E. Префиксы и суффиксы
+// получают номера в порядке добавления; строка -- множество номеров суффиксов,
+// для которых слово принадлежит языку.
+pub struct ObservationTable<'a> {
+    mat: &'a dyn Mat,
+    alphabet: Vec<char>,
+    pub prefixes: Vec<String>,
+    pub suffixes: Vec<String>,
+    prefix_to_id: HashMap<String, usize>,
+    suffix_to_id: HashMap<String, usize>,
+    pub rows: Vec<BitSet>,
+    // Номера префиксов из S
+    pub upper_prefixes: BitSet,
+    // Номера строк продолжений префикса по символам алфавита; заполнены только
+    // для префиксов из S
+    successors: Vec<Vec<usize>>,
+    // Номера префиксов из S с простыми строками
+    pub prime_prefixes: BitSet,
+    // Ответы, известные заранее; учитель о них не спрашивается
+    known_answers: HashMap<String, bool>,
+}
+
+// Содержимое таблицы без производных индексов, для контрольных точек
+#[derive(Serialize, Deserialize)]
+pub struct ObservationTableState {
+    prefixes: Vec<String>,
+    suffixes: Vec<String>,
+    rows: Vec<BitSet>,
+    upper_prefixes: BitSet,
+    successors: Vec<Vec<usize>>,
+    prime_prefixes: BitSet,
+}
+
+pub enum ClosednessCheckResult {
+    Ok,
+    UnclosedPrefix(String),
+}
+
+pub enum ConsistencyCheckResult {
+    Ok,
+    DistinguishingSuffix(String),
+}
+
+// Суффикс ε добавляется первым
+pub const EPSILON_SUFFIX_ID: usize = 0;
+
+impl<'a> ObservationTable<'a> {
+    pub fn new(mat: &'a dyn Mat, seed: &Seed) -> Result<Self> {
+        let mut table = Self {
+            mat,
+            alphabet: mat.get_alphabet().chars().collect(),
+            prefixes: Vec::new(),
+            suffixes: Vec::new(),
+            prefix_to_id: HashMap::new(),
+            suffix_to_id: HashMap::new(),
+            rows: Vec::new(),
+            upper_prefixes: BitSet::new(),
+            successors: Vec::new(),
+            prime_prefixes: BitSet::new(),
+            known_answers: seed.answers.clone(),
+        };
+
+        // Суффиксы добавляются первыми, чтобы строки префиксов запрашивались один раз
+        table.insert_suffix(EPSILON)?;
+        for suffix in &seed.suffixes {
+            table.insert_suffix(suffix)?;
+        }
+        table.insert_prefix(EPSILON)?;
+        for prefix in &seed.prefixes {
+            table.insert_prefix(prefix)?;
+        }
+
+        Ok(table)
+    }
+
+    pub fn from_state(
+        mat: &'a dyn Mat,
+        state: ObservationTableState,
+        known_answers: HashMap<String, bool>,
+    ) -> Self {
+        let to_ids = |words: &Vec<String>| {
+            HashMap::from_iter(
+                words
+                    .iter()
+                    .enumerate()
+                    .map(|(id, word)| (word.to_owned(), id)),
+            )
+        };
+
+        Self {
+            mat,
+            alphabet: mat.get_alphabet().chars().collect(),
+            prefix_to_id: to_ids(&state.prefixes),
+            suffix_to_id: to_ids(&state.suffixes),
+            prefixes: state.prefixes,
+            suffixes: state.suffixes,
+            rows: state.rows,
+            upper_prefixes: state.upper_prefixes,
+            successors: state.successors,
+            prime_prefixes: state.prime_prefixes,
+            known_answers,
+        }
+    }
+
+    pub fn get_state(&self) -> ObservationTableState {
+        ObservationTableState {
+            prefixes: self.prefixes.clone(),
+            suffixes: self.suffixes.clone(),
+            rows: self.rows.clone(),
+            upper_prefixes: self.upper_prefixes.clone(),
+            successors: self.successors.clone(),
+            prime_prefixes: self.prime_prefixes.clone(),
+        }
+    }
+
+    pub fn get_prefix_id(&self, prefix: &str) -> Option<usize> {
+        self.prefix_to_id.get(prefix).copied()
+    }
+
+    pub fn contains_upper_prefix(&self, prefix: &str) -> bool {
+        self.get_prefix_id(prefix)
+            .is_some_and(|prefix_id| self.upper_prefixes.contains(prefix_id))
+    }
+
+    pub fn contains_suffix(&self, suffix: &str) -> bool {
+        self.suffix_to_id.contains_key(suffix)
+    }
+
+    pub fn get_successor(&self, prefix_id: usize, letter_index: usize) -> usize {
+        self.successors[prefix_id][letter_index]
+    }
+
+    // Переносит префикс в S; его продолжения добавляются в нижнюю часть
+    pub fn insert_prefix(&mut self, prefix: &str) -> Result<()> {
+        if self.contains_upper_prefix(prefix) {
+            return Ok(());
+        }
+
+        let prefix_id = self.insert_row(prefix)?;
+        let mut successors = Vec::with_capacity(self.alphabet.len());
+        for letter in self.alphabet.clone() {
+            successors.push(self.insert_row(&format!("{prefix}{letter}"))?);
+        }
+        self.successors[prefix_id] = successors;
+        self.upper_prefixes.insert(prefix_id);
+
+        if let Some(equivalent_prefix_id) = self.find_equivalent_prime_prefix(prefix_id) {
+            if compare_shortlex(prefix, &self.prefixes[equivalent_prefix_id]) == Ordering::Less {
+                self.prime_prefixes.remove(equivalent_prefix_id);
+                self.prime_prefixes.insert(prefix_id);
+            }
+        } else if !self.is_covered_by_others(prefix_id) {
+            self.prime_prefixes.insert(prefix_id);
+            self.cleanup_prime_prefixes();
+        }
+
+        Ok(())
+    }
+
+    fn insert_row(&mut self, prefix: &str) -> Result<usize> {
+        if let Some(prefix_id) = self.get_prefix_id(prefix) {
+            return Ok(prefix_id);
+        }
+
+        let words: Vec<String> = self
+            .suffixes
+            .iter()
+            .map(|suffix| format!("{prefix}{suffix}"))
+            .collect();
+        let memberships = self.check_membership_batch(&words)?;
+
+        let mut row = BitSet::new();
+        for (suffix_id, is_member) in memberships.into_iter().enumerate() {
+            if is_member {
+                row.insert(suffix_id);
+            }
+        }
+
+        let prefix_id = self.prefixes.len();
+        self.prefixes.push(prefix.to_owned());
+        self.prefix_to_id.insert(prefix.to_owned(), prefix_id);
+        self.rows.push(row);
+        self.successors.push(Vec::new());
+
+        Ok(prefix_id)
+    }
+
+    // Новый столбец заполняется сразу для обеих частей таблицы
+    pub fn insert_suffix(&mut self, suffix: &str) -> Result<()> {
+        if self.contains_suffix(suffix) {
+            return Ok(());
+        }
+
+        let words: Vec<String> = self
+            .prefixes
+            .iter()
+            .map(|prefix| format!("{prefix}{suffix}"))
+            .collect();
+        let memberships = self.check_membership_batch(&words)?;
+
+        let suffix_id = self.suffixes.len();
+        self.suffixes.push(suffix.to_owned());
+        self.suffix_to_id.insert(suffix.to_owned(), suffix_id);
+
+        for (row, is_member) in self.rows.iter_mut().zip(memberships) {
+            if is_member {
+                row.insert(suffix_id);
+            }
+        }
+
+        self.rebuild_prime_prefixes();
+
+        Ok(())
+    }
+
+    // Учителю отправляются только слова с неизвестными ответами
+    fn check_membership_batch(&self, words: &[String]) -> Result<Vec<bool>> {
+        let unknown_words: Vec<String> = words
+            .iter()
+            .filter(|word| !self.known_answers.contains_key(*word))
+            .cloned()
+            .collect();
+        let mut answers = self.mat.check_membership_batch(&unknown_words)?.into_iter();
+
+        Ok(words
+            .iter()
+            .map(|word| match self.known_answers.get(word) {
+                Some(answer) => *answer,
+                None => answers.next().unwrap(),
+            })
+            .collect())
+    }
+
+    fn find_equivalent_prime_prefix(&self, prefix_id: usize) -> Option<usize> {
+        self.prime_prefixes
+            .iter()
+            .find(|prime_prefix_id| self.rows[*prime_prefix_id] == self.rows[prefix_id])
+    }
+
+    pub fn is_prime(&self, prefix_id: usize) -> bool {
+        self.prime_prefixes.contains(prefix_id)
+    }
+
+    // Строка покрыта, если она равна объединению вложенных в неё простых строк
+    pub fn is_covered(&self, row: &BitSet) -> bool {
+        self.get_join(row, None) == *row
+    }
+
+    // Пустая строка считается простой
+    fn is_covered_by_others(&self, prefix_id: usize) -> bool {
+        let row = &self.rows[prefix_id];
+        !row.is_empty() && self.get_join(row, Some(prefix_id)) == *row
+    }
+
+    fn get_join(&self, row: &BitSet, excluded_prefix_id: Option<usize>) -> BitSet {
+        let mut join = BitSet::new();
+        for prime_prefix_id in self.prime_prefixes.iter() {
+            let prime_row = &self.rows[prime_prefix_id];
+            if Some(prime_prefix_id) != excluded_prefix_id && prime_row.is_subset(row) {
+                join.union_with(prime_row);
+            }
+        }
+        join
+    }
+
+    fn cleanup_prime_prefixes(&mut self) {
+        let not_prime_prefixes_anymore: Vec<usize> = self
+            .prime_prefixes
+            .iter()
+            .filter(|prefix_id| self.is_covered_by_others(*prefix_id))
+            .collect();
+
+        for prefix_id in not_prime_prefixes_anymore {
+            self.prime_prefixes.remove(prefix_id);
+        }
+    }
+
+    fn rebuild_prime_prefixes(&mut self) {
+        let upper_prefix_ids: Vec<usize> = self.upper_prefixes.iter().collect();
+        for prefix_id in upper_prefix_ids {
+            if !self.is_covered_by_others(prefix_id) {
+                self.prime_prefixes.insert(prefix_id);
+            }
+        }
+        self.cleanup_prime_prefixes();
+    }
+
+    // Номера простых строк, вложенных в данную
+    pub fn get_absorbed_prime_prefixes(&self, row: &BitSet) -> Vec<usize> {
+        self.prime_prefixes
+            .iter()
+            .filter(|prefix_id| self.rows[*prefix_id].is_subset(row))
+            .collect()
+    }
+
+    // RFSA-замкнутость: каждая строка нижней части покрыта простыми строками
+    pub fn check_closedness(&self) -> ClosednessCheckResult {
+        for (prefix_id, row) in self.rows.iter().enumerate() {
+            if !self.upper_prefixes.contains(prefix_id) && !self.is_covered(row) {
+                return ClosednessCheckResult::UnclosedPrefix(self.prefixes[prefix_id].to_owned());
+            }
+        }
+
+        ClosednessCheckResult::Ok
+    }
+
+    // RFSA-согласованность: вложение строк префиксов из S сохраняется для их
+    // продолжений по каждому символу
+    pub fn check_consistency(&self) -> ConsistencyCheckResult {
+        for prefix_id_1 in self.upper_prefixes.iter() {
+            for prefix_id_2 in self.upper_prefixes.iter() {
+                if prefix_id_1 == prefix_id_2
+                    || !self.rows[prefix_id_1].is_subset(&self.rows[prefix_id_2])
+                {
+                    continue;
+                }
+
+                for (index, letter) in self.alphabet.iter().enumerate() {
+                    let new_row_1 = &self.rows[self.get_successor(prefix_id_1, index)];
+                    let new_row_2 = &self.rows[self.get_successor(prefix_id_2, index)];
+
+                    if let Some(suffix_id) = new_row_1.first_difference(new_row_2) {
+                        let suffix = &self.suffixes[suffix_id];
+                        return ConsistencyCheckResult::DistinguishingSuffix(format!(
+                            "{letter}{suffix}"
+                        ));
+                    }
+                }
+            }
+        }
+
+        ConsistencyCheckResult::Ok
+    }
+
+    pub fn get_size(&self) -> TableSize {
+        let upper = self.upper_prefixes.iter().count();
+        TableSize {
+            upper,
+            lower: self.prefixes.len() - upper,
+            suffixes: self.suffixes.len(),
+        }
+    }
+
+    pub fn get_view(&self) -> TableView {
+        let upper_rows = self.upper_prefixes.iter();
+        let lower_rows = (0..self.rows.len()).filter(|prefix_id| !self.upper_prefixes.contains(*prefix_id));
+
+        TableView {
+            suffixes: self.suffixes.clone(),
+            rows: upper_rows
+                .chain(lower_rows)
+                .map(|prefix_id| TableRow {
+                    prefix: self.prefixes[prefix_id].to_owned(),
+                    is_upper: self.upper_prefixes.contains(prefix_id),
+                    is_prime: self.is_prime(prefix_id),
+                    cells: (0..self.suffixes.len())
+                        .map(|suffix_id| self.rows[prefix_id].contains(suffix_id))
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use serde::Serialize;
+
+#[derive(Clone, Copy)]
+pub enum TableFormat {
+    // Выровненные столбцы для терминала
+    Text,
+    Csv,
+    // Окружение tabular для отчётов
+    Latex,
+}
+
+// Снимок таблицы наблюдений: сначала строки S, затем строки S·Σ
+#[derive(Serialize)]
+pub struct TableView {
+    pub suffixes: Vec<String>,
+    pub rows: Vec<TableRow>,
+}
+
+#[derive(Serialize)]
+pub struct TableRow {
+    pub prefix: String,
+    pub is_upper: bool,
+    pub is_prime: bool,
+    pub cells: Vec<bool>,
+}
+
+impl TableView {
+    pub fn render(&self, format: TableFormat) -> String {
+        match format {
+            TableFormat::Text => self.render_text(),
+            TableFormat::Csv => self.render_csv(),
+            TableFormat::Latex => self.render_latex(),
+        }
+    }
+
+    // Простые строки отмечены `*`, нижняя часть отделена чертой
+    fn render_text(&self) -> String {
+        let mut lines = vec![Vec::from_iter(
+            std::iter::once(String::new()).chain(self.suffixes.iter().map(|suffix| show(suffix))),
+        )];
+        for row in &self.rows {
+            let marker = if row.is_prime { "*" } else { " " };
+            lines.push(Vec::from_iter(
+                std::iter::once(format!("{marker}{}", show(&row.prefix)))
+                    .chain(row.cells.iter().map(|cell| (*cell as u8).to_string())),
+            ));
+        }
+
+        let widths: Vec<usize> = (0..lines[0].len())
+            .map(|column| {
+                lines
+                    .iter()
+                    .map(|line| line[column].chars().count())
+                    .max()
+                    .unwrap()
+            })
+            .collect();
+        let separator_index = self.rows.iter().take_while(|row| row.is_upper).count() + 1;
+
+        let mut table = String::new();
+        for (index, line) in lines.iter().enumerate() {
+            if index == separator_index {
+                let width = widths.iter().sum::<usize>() + 2 * (widths.len() - 1);
+                table.push_str(&"-".repeat(width));
+                table.push('\n');
+            }
+
+            let cells: Vec<String> = line
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect();
+            table.push_str(cells.join("  ").trim_end());
+            table.push('\n');
+        }
+
+        table
+    }
+
+    fn render_csv(&self) -> String {
+        let header = ["prefix", "part", "prime"]
+            .into_iter()
+            .map(String::from)
+            .chain(self.suffixes.iter().map(|suffix| escape_csv(&show(suffix))));
+
+        let mut table = String::new();
+        table.push_str(&Vec::from_iter(header).join(","));
+        table.push('\n');
+
+        for row in &self.rows {
+            let part = if row.is_upper { "upper" } else { "lower" };
+            let fields = [
+                escape_csv(&show(&row.prefix)),
+                part.to_string(),
+                (row.is_prime as u8).to_string(),
+            ]
+            .into_iter()
+            .chain(row.cells.iter().map(|cell| (*cell as u8).to_string()));
+            table.push_str(&Vec::from_iter(fields).join(","));
+            table.push('\n');
+        }
+
+        table
+    }
+
+    // Простые строки отмечены звёздочкой, части таблицы разделены \hline
+    fn render_latex(&self) -> String {
+        let mut table = format!(
+            "\\begin{{tabular}}{{l|{}}}\n",
+            "c".repeat(self.suffixes.len())
+        );
+
+        let header: Vec<String> = self
+            .suffixes
+            .iter()
+            .map(|suffix| format!("${}$", to_latex(suffix)))
+            .collect();
+        table.push_str(&format!("   & {} \\\\\n  \\hline\n", header.join(" & ")));
+
+        for (index, row) in self.rows.iter().enumerate() {
+            if index > 0 && self.rows[index - 1].is_upper && !row.is_upper {
+                table.push_str("  \\hline\n");
+            }
+
+            let marker = if row.is_prime { "^*" } else { "" };
+            let cells: Vec<String> = row
+                .cells
+                .iter()
+                .map(|cell| (*cell as u8).to_string())
+                .collect();
+            table.push_str(&format!(
+                "  ${}{marker}$ & {} \\\\\n",
+                to_latex(&row.prefix),
+                cells.join(" & ")
+            ));
+        }
+
+        table.push_str("\\end{tabular}\n");
+        table
+    }
+}
+
+fn show(word: &str) -> String {
+    if word.is_empty() {
+        "ε".to_string()
+    } else {
+        word.to_owned()
+    }
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn to_latex(word: &str) -> String {
+    if word.is_empty() {
+        return "\\varepsilon".to_string();
+    }
+
+    word.chars()
+        .map(|letter| match letter {
+            '#' | '$' | '%' | '&' | '_' | '{' | '}' => format!("\\{letter}"),
+            '\\' => "\\backslash ".to_string(),
+            '^' => "\\hat{}".to_string(),
+            '~' => "\\sim ".to_string(),
+            letter => letter.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_view() -> TableView {
+        let row = |prefix: &str, is_upper, is_prime, cells: [bool; 2]| TableRow {
+            prefix: prefix.to_string(),
+            is_upper,
+            is_prime,
+            cells: cells.to_vec(),
+        };
+
+        TableView {
+            suffixes: vec![String::new(), "a".to_string()],
+            rows: vec![
+                row("", true, true, [false, true]),
+                row("a", true, true, [true, false]),
+                row("b", false, false, [false, true]),
+            ],
+        }
+    }
+
+    #[test]
+    fn renders_text() {
+        assert_eq!(
+            get_view().render(TableFormat::Text),
+            "    ε  a\n*ε  0  1\n*a  1  0\n--------\n b  0  1\n"
+        );
+    }
+
+    #[test]
+    fn renders_csv() {
+        assert_eq!(
+            get_view().render(TableFormat::Csv),
+            "prefix,part,prime,ε,a\nε,upper,1,0,1\na,upper,1,1,0\nb,lower,0,0,1\n"
+        );
+        assert_eq!(escape_csv("a,b"), "\"a,b\"");
+        assert_eq!(escape_csv("a\"b"), "\"a\"\"b\"");
+    }
+
+    #[test]
+    fn renders_latex() {
+        assert_eq!(
+            get_view().render(TableFormat::Latex),
+            "\\begin{tabular}{l|cc}\n   & $\\varepsilon$ & $a$ \\\\\n  \\hline\n  \
+             $\\varepsilon^*$ & 0 & 1 \\\\\n  $a^*$ & 1 & 0 \\\\\n  \\hline\n  \
+             $b$ & 0 & 1 \\\\\n\\end{tabular}\n"
+        );
+        assert_eq!(to_latex("a_#^"), "a\\_\\#\\hat{}");
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use crate::automata::{compare_shortlex, get_automata_impl, Automata, AutomataImpl};
+use crate::error::{Error, Result};
+use crate::mat::conformance::Characterization;
+use crate::mat::Mat;
+
+// Начальное содержимое таблицы NL*, взятое из прошлого запуска: префиксы
+// попадают в S, суффиксы -- в E, а на запросы о словах выборки таблица
+// отвечает сама, после того как выборка сверена с учителем
+#[derive(Clone, Default)]
+pub struct Seed {
+    pub prefixes: Vec<String>,
+    pub suffixes: Vec<String>,
+    pub answers: HashMap<String, bool>,
+}
+
+impl Seed {
+    pub fn is_empty(&self) -> bool {
+        self.prefixes.is_empty() && self.suffixes.is_empty() && self.answers.is_empty()
+    }
+
+    // Слова доступа и различающие суффиксы минимального ДКА автомата
+    pub fn from_automata(automata: &AutomataImpl, alphabet: &str) -> Self {
+        let dfa = automata.determinize();
+        let dfa = get_automata_impl(dfa.as_ref());
+        let characterization = Characterization::new(&dfa.minimize(alphabet), alphabet);
+
+        Self {
+            prefixes: characterization.get_state_cover(),
+            suffixes: Vec::from_iter(characterization.get_characterization_set()),
+            answers: HashMap::new(),
+        }
+    }
+
+    // Формат выборки: по слову на строку, перед словом `+`, если оно принадлежит
+    // языку, и `-`, если нет; `ε` обозначает пустое слово. Пустые строки и строки
+    // с `#` пропускаются.
+    pub fn from_sample(text: &str, alphabet: &str) -> Result<Self> {
+        let mut answers = HashMap::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let (label, word) = match tokens.as_slice() {
+                [] => continue,
+                [comment, ..] if comment.starts_with('#') => continue,
+                [label] => (*label, ""),
+                [label, "ε"] => (*label, ""),
+                [label, word] => (*label, *word),
+                _ => {
+                    return Err(Error::Seed(format!(
+                        "line {}: invalid line '{line}'",
+                        index + 1
+                    )))
+                }
+            };
+
+            let answer = match label {
+                "+" => true,
+                "-" => false,
+                _ => {
+                    return Err(Error::Seed(format!(
+                        "line {}: expected '+' or '-' instead of '{label}'",
+                        index + 1
+                    )))
+                }
+            };
+            if word.chars().any(|letter| !alphabet.contains(letter)) {
+                return Err(Error::Seed(format!(
+                    "line {}: '{word}' is not a word over {alphabet:?}",
+                    index + 1
+                )));
+            }
+
+            answers.insert(word.to_owned(), answer);
+        }
+
+        Ok(Self {
+            answers,
+            ..Default::default()
+        })
+    }
+
+    // Ответы выборки сверяются с учителем одним пакетным запросом: неверный
+    // ответ в таблице не исправил бы ни один контрпример
+    pub fn verify(&self, mat: &dyn Mat) -> Result<()> {
+        if self.answers.is_empty() {
+            return Ok(());
+        }
+
+        let mut words = Vec::from_iter(self.answers.keys().cloned());
+        words.sort_by(|first, second| compare_shortlex(first, second));
+        let answers = mat.check_membership_batch(&words)?;
+        let contradictions: Vec<String> = words
+            .into_iter()
+            .zip(answers)
+            .filter(|(word, answer)| self.answers[word] != *answer)
+            .map(|(word, answer)| {
+                let word = if word.is_empty() { "ε" } else { &word };
+                format!("{}{word}", if answer { '+' } else { '-' })
+            })
+            .collect();
+
+        if contradictions.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Seed(format!(
+                "the teacher answers {} contrary to the sample",
+                contradictions.join(", ")
+            )))
+        }
+    }
+
+    pub fn merge(mut self, other: Self) -> Self {
+        self.prefixes.extend(other.prefixes);
+        self.suffixes.extend(other.suffixes);
+        self.answers.extend(other.answers);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sample() {
+        let text = "# выборка\n+ ab\n- b\n\n+ ε\n-\n  + aab  \n";
+        let seed = Seed::from_sample(text, "ab").unwrap();
+
+        assert!(seed.prefixes.is_empty() && seed.suffixes.is_empty());
+        // Последний ответ о пустом слове заменяет предыдущий
+        assert_eq!(
+            seed.answers,
+            HashMap::from([
+                ("ab".to_string(), true),
+                ("b".to_string(), false),
+                ("".to_string(), false),
+                ("aab".to_string(), true),
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_lines() {
+        for (text, message) in [
+            ("+ a\n+ a b", "line 2: invalid line"),
+            ("* a", "line 1: expected '+' or '-'"),
+            ("+ ac", "line 1: 'ac' is not a word"),
+        ] {
+            match Seed::from_sample(text, "ab") {
+                Err(Error::Seed(error)) => assert!(error.starts_with(message), "{error}"),
+                _ => panic!("{text} is accepted"),
+            }
+        }
+    }
+
+    #[test]
+    fn takes_cover_and_suffixes_from_automata() {
+        let automata = crate::regex::parse("(a|b)*a").unwrap();
+        let seed = Seed::from_automata(&automata, "ab");
+
+        assert_eq!(seed.prefixes, ["", "a"]);
+        assert_eq!(seed.suffixes, [""]);
+        assert!(seed.answers.is_empty());
+    }
+
+    #[test]
+    fn verifies_sample_against_teacher() {
+        let mat = crate::mat::reference::ReferenceMat::from_regex("ab", "(a|b)*a").unwrap();
+
+        let seed = Seed::from_sample("+ a\n+ ba\n- ε\n- ab", "ab").unwrap();
+        assert!(seed.verify(&mat).is_ok());
+
+        // Ответы учителя перечисляются в shortlex-порядке
+        let seed = Seed::from_sample("+ b\n- ba\n+ ε\n- a", "ab").unwrap();
+        match seed.verify(&mat) {
+            Err(Error::Seed(error)) => assert_eq!(
+                error,
+                "the teacher answers -ε, +a, -b, +ba contrary to the sample"
+            ),
+            _ => panic!("contradicting sample is accepted"),
+        }
+    }
+}
@@ -0,0 +1,395 @@
+use crate::automata::{AutomataImpl, EPSILON, START};
+
+// Регулярные выражения над символами алфавита: альтернатива `|`, итерации
+// `*`, `+`, `?`, скобки, `ε` для пустого слова и `∅` для пустого языка.
+// Пробелы игнорируются.
+
+const SPECIAL_LETTERS: &str = "|*+?()";
+
+struct Fragment {
+    start: usize,
+    end: usize,
+}
+
+struct Builder {
+    size: usize,
+    transitions: Vec<(usize, String, usize)>,
+}
+
+impl Builder {
+    fn add_state(&mut self) -> usize {
+        self.size += 1;
+        self.size - 1
+    }
+
+    fn add_transition(&mut self, from: usize, label: &str, to: usize) {
+        self.transitions.push((from, label.to_owned(), to));
+    }
+
+    fn letter(&mut self, label: &str) -> Fragment {
+        let start = self.add_state();
+        let end = self.add_state();
+        self.add_transition(start, label, end);
+        Fragment { start, end }
+    }
+
+    // Фрагмент без переходов: из начала конец недостижим
+    fn empty(&mut self) -> Fragment {
+        let start = self.add_state();
+        let end = self.add_state();
+        Fragment { start, end }
+    }
+
+    fn concatenation(&mut self, first: Fragment, second: Fragment) -> Fragment {
+        self.add_transition(first.end, EPSILON, second.start);
+        Fragment {
+            start: first.start,
+            end: second.end,
+        }
+    }
+
+    fn alternative(&mut self, first: Fragment, second: Fragment) -> Fragment {
+        let start = self.add_state();
+        let end = self.add_state();
+        self.add_transition(start, EPSILON, first.start);
+        self.add_transition(start, EPSILON, second.start);
+        self.add_transition(first.end, EPSILON, end);
+        self.add_transition(second.end, EPSILON, end);
+        Fragment { start, end }
+    }
+
+    fn iteration(&mut self, fragment: Fragment, skippable: bool, repeatable: bool) -> Fragment {
+        let start = self.add_state();
+        let end = self.add_state();
+        self.add_transition(start, EPSILON, fragment.start);
+        self.add_transition(fragment.end, EPSILON, end);
+        if skippable {
+            self.add_transition(start, EPSILON, end);
+        }
+        if repeatable {
+            self.add_transition(fragment.end, EPSILON, fragment.start);
+        }
+        Fragment { start, end }
+    }
+}
+
+struct Parser<'a> {
+    letters: std::iter::Peekable<std::str::Chars<'a>>,
+    builder: Builder,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&mut self) -> Option<char> {
+        while let Some(letter) = self.letters.peek() {
+            if !letter.is_whitespace() {
+                return Some(*letter);
+            }
+            self.letters.next();
+        }
+        None
+    }
+
+    fn parse_alternative(&mut self) -> Result<Fragment, String> {
+        let mut fragment = self.parse_concatenation()?;
+        while let Some('|') = self.peek() {
+            self.letters.next();
+            let next_fragment = self.parse_concatenation()?;
+            fragment = self.builder.alternative(fragment, next_fragment);
+        }
+        Ok(fragment)
+    }
+
+    fn parse_concatenation(&mut self) -> Result<Fragment, String> {
+        let mut fragment = self.builder.letter(EPSILON);
+        while let Some(letter) = self.peek() {
+            if letter == '|' || letter == ')' {
+                break;
+            }
+            let next_fragment = self.parse_iteration()?;
+            fragment = self.builder.concatenation(fragment, next_fragment);
+        }
+        Ok(fragment)
+    }
+
+    fn parse_iteration(&mut self) -> Result<Fragment, String> {
+        let mut fragment = self.parse_atom()?;
+        while let Some(letter) = self.peek() {
+            fragment = match letter {
+                '*' => self.builder.iteration(fragment, true, true),
+                '+' => self.builder.iteration(fragment, false, true),
+                '?' => self.builder.iteration(fragment, true, false),
+                _ => break,
+            };
+            self.letters.next();
+        }
+        Ok(fragment)
+    }
+
+    fn parse_atom(&mut self) -> Result<Fragment, String> {
+        match self.letters.next() {
+            Some('(') => {
+                let fragment = self.parse_alternative()?;
+                match self.peek() {
+                    Some(')') => {
+                        self.letters.next();
+                        Ok(fragment)
+                    }
+                    _ => Err("expected ')'".to_string()),
+                }
+            }
+            Some('ε') => Ok(self.builder.letter(EPSILON)),
+            Some('∅') => Ok(self.builder.empty()),
+            Some(letter) if !SPECIAL_LETTERS.contains(letter) => {
+                Ok(self.builder.letter(&letter.to_string()))
+            }
+            Some(letter) => Err(format!("unexpected '{letter}'")),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+pub fn parse(regex: &str) -> Result<AutomataImpl, String> {
+    let mut parser = Parser {
+        letters: regex.chars().peekable(),
+        builder: Builder {
+            size: 0,
+            transitions: Vec::new(),
+        },
+    };
+
+    let start = parser.builder.add_state();
+    let fragment = parser.parse_alternative()?;
+    if let Some(letter) = parser.peek() {
+        return Err(format!("unexpected '{letter}'"));
+    }
+    parser.builder.add_transition(start, EPSILON, fragment.start);
+
+    let mut automata = AutomataImpl::new(parser.builder.size);
+    for (from, label, to) in parser.builder.transitions {
+        automata.transitions[from][to].insert(label);
+    }
+    automata.finite_states[fragment.end] = true;
+
+    debug_assert_eq!(start, START);
+    Ok(automata)
+}
+
+#[derive(Clone, PartialEq)]
+enum Expression {
+    Epsilon,
+    Letter(String),
+    Concatenation(Vec<Expression>),
+    Alternative(Vec<Expression>),
+    Iteration(Box<Expression>),
+}
+
+impl Expression {
+    fn concatenation(first: Self, second: Self) -> Self {
+        let mut items = Vec::new();
+        for item in [first, second] {
+            match item {
+                Self::Epsilon => {}
+                Self::Concatenation(inner) => items.extend(inner),
+                item => items.push(item),
+            }
+        }
+
+        match items.len() {
+            0 => Self::Epsilon,
+            1 => items.pop().unwrap(),
+            _ => Self::Concatenation(items),
+        }
+    }
+
+    fn alternative(first: Self, second: Self) -> Self {
+        let mut items = Vec::new();
+        for item in [first, second] {
+            let inner = match item {
+                Self::Alternative(inner) => inner,
+                item => vec![item],
+            };
+            for item in inner {
+                if !items.contains(&item) {
+                    items.push(item);
+                }
+            }
+        }
+
+        match items.len() {
+            1 => items.pop().unwrap(),
+            _ => Self::Alternative(items),
+        }
+    }
+
+    fn iteration(self) -> Self {
+        match self {
+            Self::Epsilon => Self::Epsilon,
+            Self::Iteration(_) => self,
+            _ => Self::Iteration(Box::new(self)),
+        }
+    }
+
+    fn is_atomic(&self) -> bool {
+        matches!(self, Self::Epsilon | Self::Letter(_) | Self::Iteration(_))
+    }
+}
+
+impl std::fmt::Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Epsilon => write!(f, "ε"),
+            Self::Letter(letter) => write!(f, "{letter}"),
+            Self::Concatenation(items) => {
+                for item in items {
+                    if matches!(item, Self::Alternative(_)) {
+                        write!(f, "({item})")?;
+                    } else {
+                        write!(f, "{item}")?;
+                    }
+                }
+                Ok(())
+            }
+            Self::Alternative(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                write!(f, "{}", items.join("|"))
+            }
+            Self::Iteration(item) if item.is_atomic() => write!(f, "{item}*"),
+            Self::Iteration(item) => write!(f, "({item})*"),
+        }
+    }
+}
+
+// Регулярное выражение языка автомата, полученное исключением состояний.
+// Для пустого языка возвращается `∅`.
+pub fn from_automata(automata: &AutomataImpl) -> String {
+    // Состояния автомата дополняются новыми начальным и заключительным
+    let start = automata.size;
+    let end = automata.size + 1;
+    let size = automata.size + 2;
+
+    let mut expressions = vec![vec![None::<Expression>; size]; size];
+    let mut add = |from: usize, to: usize, expression: Expression| {
+        let cell = &mut expressions[from][to];
+        *cell = Some(match cell.take() {
+            Some(previous) => Expression::alternative(previous, expression),
+            None => expression,
+        });
+    };
+
+    add(start, START, Expression::Epsilon);
+    for from in 0..automata.size {
+        for (to, labels) in automata.transitions[from].iter().enumerate() {
+            for label in labels {
+                let expression = if label.eq(EPSILON) {
+                    Expression::Epsilon
+                } else {
+                    Expression::Letter(label.to_owned())
+                };
+                add(from, to, expression);
+            }
+        }
+        if automata.finite_states[from] {
+            add(from, end, Expression::Epsilon);
+        }
+    }
+
+    for state in 0..automata.size {
+        let loop_expression = expressions[state][state].take().map(Expression::iteration);
+
+        for from in (0..size).filter(|from| *from != state) {
+            let Some(incoming) = expressions[from][state].clone() else {
+                continue;
+            };
+            for to in (0..size).filter(|to| *to != state) {
+                let Some(outgoing) = expressions[state][to].clone() else {
+                    continue;
+                };
+
+                let mut expression = incoming.clone();
+                if let Some(loop_expression) = &loop_expression {
+                    expression = Expression::concatenation(expression, loop_expression.clone());
+                }
+                expression = Expression::concatenation(expression, outgoing);
+
+                expressions[from][to] = Some(match expressions[from][to].take() {
+                    Some(previous) => Expression::alternative(previous, expression),
+                    None => expression,
+                });
+            }
+        }
+
+        for row in expressions.iter_mut() {
+            row[state] = None;
+        }
+        expressions[state] = vec![None; size];
+    }
+
+    match &expressions[start][end] {
+        Some(expression) => expression.to_string(),
+        None => "∅".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automata::{get_automata_impl, Automata};
+
+    fn accepts(regex: &str, words: &[&str]) -> Vec<bool> {
+        let dfa = parse(regex).unwrap().determinize();
+        words
+            .iter()
+            .map(|word| dfa.check_membership(word))
+            .collect()
+    }
+
+    #[test]
+    fn parses_operators() {
+        let words = ["", "a", "b", "ab", "aab", "abab"];
+        let cases = [
+            ("a", [false, true, false, false, false, false]),
+            ("a|b", [false, true, true, false, false, false]),
+            ("a*b", [false, false, true, true, true, false]),
+            ("a+b", [false, false, false, true, true, false]),
+            ("a?b", [false, false, true, true, false, false]),
+            ("(ab)*", [true, false, false, true, false, true]),
+            ("ε|a", [true, true, false, false, false, false]),
+            (" a b ", [false, false, false, true, false, false]),
+            // Пустая альтернатива обозначает пустое слово
+            ("a|", [true, true, false, false, false, false]),
+            ("∅", [false, false, false, false, false, false]),
+            ("∅|ab", [false, false, false, true, false, false]),
+            ("a∅", [false, false, false, false, false, false]),
+            ("∅*", [true, false, false, false, false, false]),
+        ];
+
+        for (regex, expected) in cases {
+            assert_eq!(accepts(regex, &words), expected, "{regex}");
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        for regex in ["(a", "a)", "*a", "a|(", "a**|+"] {
+            assert!(parse(regex).is_err(), "{regex}");
+        }
+    }
+
+    #[test]
+    fn restores_expression_from_automata() {
+        for regex in ["(a|b)*a", "(ab|ba)*", "a*b+|ε", "∅"] {
+            let automata = parse(regex).unwrap();
+            let restored = parse(&from_automata(&automata)).unwrap();
+
+            let dfa = automata.determinize();
+            let restored_dfa = restored.determinize();
+            let dfa = get_automata_impl(dfa.as_ref());
+            let restored_dfa = get_automata_impl(restored_dfa.as_ref());
+            assert_eq!(
+                dfa.find_distinguishing_word(restored_dfa, "ab"),
+                None,
+                "{regex}"
+            );
+        }
+    }
+}
@@ -0,0 +1,408 @@
+mod discrimination_tree;
+
+use crate::automata::{Automata, AutomataImpl, EPSILON, START};
+use crate::error::Result;
+use crate::learner::{Learner, RoundResult};
+use crate::mat::{EquivalenceCheckResult, Mat};
+use crate::ttt::discrimination_tree::{DiscriminationTree, Node, ROOT};
+
+// Алгоритм TTT: состояния гипотезы образуют остовное дерево, классы состояний
+// хранятся в дереве различения. Длинные суффиксы контрпримеров становятся лишь
+// временными различителями и заменяются короткими окончательными, поэтому
+// размер структур не зависит от суммарной длины контрпримеров.
+pub struct TttImpl<'a> {
+    mat: &'a dyn Mat,
+    alphabet: Vec<char>,
+    states: Vec<State>,
+    transitions: Vec<Vec<Transition>>,
+    tree: DiscriminationTree,
+}
+
+struct State {
+    access_word: String,
+    leaf: usize,
+}
+
+#[derive(Clone, Copy)]
+enum Transition {
+    // Переход остовного дерева: слово доступа цели получено из слова доступа
+    // источника дописыванием символа
+    Tree(usize),
+    // Прочие переходы указывают на узел дерева различения и просеиваются до листа
+    NonTree(usize),
+}
+
+impl<'a> Learner for TttImpl<'a> {
+    fn run_round(&mut self) -> Result<RoundResult> {
+        let mut counterexample: Option<(String, bool)> = None;
+        loop {
+            self.close_transitions()?;
+            while self.finalize_discriminator()? {
+                self.close_transitions()?;
+            }
+
+            let dfa = self.build_dfa();
+
+            // Гипотеза может расходиться с ответами, сохранёнными в дереве
+            // различения; такие слова являются контрпримерами без запроса
+            // эквивалентности
+            if let Some((word, is_member)) = self.find_inconsistency(&dfa) {
+                self.process_counterexample(&word, is_member, &dfa)?;
+                continue;
+            }
+
+            // Одного разбиения контрпримера может не хватить: он разбирается
+            // снова, пока гипотеза не классифицирует его верно
+            if let Some((word, is_member)) = &counterexample {
+                if dfa.check_membership(word) == *is_member {
+                    break Ok(RoundResult::Counterexample(word.to_owned()));
+                }
+                self.process_counterexample(word, *is_member, &dfa)?;
+                continue;
+            }
+
+            let EquivalenceCheckResult::Counterexample(word) = self.mat.check_equivalence(&dfa)?
+            else {
+                break Ok(RoundResult::Finished(Box::new(dfa)));
+            };
+
+            let is_member = self.mat.check_membership(&word)?;
+            counterexample = Some((word, is_member));
+        }
+    }
+}
+
+impl<'a> TttImpl<'a> {
+    pub fn new(mat: &'a dyn Mat) -> Result<Self> {
+        let alphabet: Vec<char> = mat.get_alphabet().chars().collect();
+        let is_start_accepted = mat.check_membership(EPSILON)?;
+        let tree = DiscriminationTree::new(START, is_start_accepted);
+        let leaf = tree.get_children(ROOT)[is_start_accepted as usize];
+
+        Ok(Self {
+            mat,
+            states: vec![State {
+                access_word: EPSILON.to_owned(),
+                leaf,
+            }],
+            transitions: vec![vec![Transition::NonTree(ROOT); alphabet.len()]],
+            alphabet,
+            tree,
+        })
+    }
+
+    fn add_state(&mut self, access_word: String, leaf: usize) -> usize {
+        self.states.push(State { access_word, leaf });
+        self.transitions
+            .push(vec![Transition::NonTree(ROOT); self.alphabet.len()]);
+        self.states.len() - 1
+    }
+
+    // Просеивает переходы до листьев; лист без состояния даёт новое состояние
+    fn close_transitions(&mut self) -> Result<()> {
+        let mut state = START;
+        while state < self.states.len() {
+            for (index, letter) in self.alphabet.clone().into_iter().enumerate() {
+                let Transition::NonTree(mut node) = self.transitions[state][index] else {
+                    continue;
+                };
+
+                let word = format!("{}{letter}", self.states[state].access_word);
+                while !self.tree.is_leaf(node) {
+                    let discriminator = self.tree.get_discriminator(node);
+                    let is_member = self.mat.check_membership(&format!("{word}{discriminator}"))?;
+                    node = self.tree.get_children(node)[is_member as usize];
+                }
+
+                self.transitions[state][index] = match self.tree.nodes[node] {
+                    Node::Leaf { state: Some(_) } => Transition::NonTree(node),
+                    _ => {
+                        let new_state = self.add_state(word, node);
+                        self.tree.nodes[node] = Node::Leaf {
+                            state: Some(new_state),
+                        };
+                        Transition::Tree(new_state)
+                    }
+                };
+            }
+            state += 1;
+        }
+
+        Ok(())
+    }
+
+    fn get_target(&self, state: usize, index: usize) -> usize {
+        match self.transitions[state][index] {
+            Transition::Tree(target) => target,
+            Transition::NonTree(node) => match self.tree.nodes[node] {
+                Node::Leaf {
+                    state: Some(target),
+                } => target,
+                _ => panic!("transition is not closed"),
+            },
+        }
+    }
+
+    fn build_dfa(&self) -> AutomataImpl {
+        let mut automata = AutomataImpl::new(self.states.len());
+
+        for (state, data) in self.states.iter().enumerate() {
+            for (index, letter) in self.alphabet.iter().enumerate() {
+                let target = self.get_target(state, index);
+                automata.transitions[state][target].insert(letter.to_string());
+            }
+
+            // Последний предок листа -- корень с различителем ε
+            let (_, is_accepted) = *self.tree.get_ancestors(data.leaf).last().unwrap();
+            automata.finite_states[state] = is_accepted;
+        }
+
+        automata
+    }
+
+    fn find_inconsistency(&self, dfa: &AutomataImpl) -> Option<(String, bool)> {
+        for state in &self.states {
+            for (node, is_member) in self.tree.get_ancestors(state.leaf) {
+                let discriminator = self.tree.get_discriminator(node);
+                let word = format!("{}{discriminator}", state.access_word);
+                if dfa.check_membership(&word) != is_member {
+                    return Some((word, is_member));
+                }
+            }
+        }
+
+        None
+    }
+
+    // Разбор контрпримера по Ривесту–Шапиру: находится разбиение u a v, для
+    // которого ответы на ⌊u⌋ a v и ⌊u a⌋ v различаются. Переход из ⌊u⌋ по a
+    // становится переходом остовного дерева в новое состояние, отделённое от
+    // прежней цели временным различителем v.
+    fn process_counterexample(
+        &mut self,
+        counterexample: &str,
+        is_member: bool,
+        dfa: &AutomataImpl,
+    ) -> Result<()> {
+        let splits: Vec<usize> = counterexample
+            .char_indices()
+            .map(|(index, _)| index)
+            .chain(std::iter::once(counterexample.len()))
+            .collect();
+
+        let get_answer = |index: usize| {
+            let (prefix, suffix) = counterexample.split_at(splits[index]);
+            let state = dfa.get_state(prefix).unwrap();
+            let access_word = &self.states[state].access_word;
+            self.mat.check_membership(&format!("{access_word}{suffix}"))
+        };
+
+        let mut low = 0;
+        let mut high = splits.len() - 1;
+        while high - low > 1 {
+            let middle = (low + high) / 2;
+            if get_answer(middle)? == is_member {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+
+        let source = dfa.get_state(&counterexample[..splits[low]]).unwrap();
+        let letter = counterexample[splits[low]..].chars().next().unwrap();
+        let index = self.alphabet.iter().position(|other| *other == letter).unwrap();
+        let discriminator = &counterexample[splits[high]..];
+
+        let Transition::NonTree(_) = self.transitions[source][index] else {
+            // Цель перехода остовного дерева совпадает со словом ⌊u⌋ a, поэтому
+            // разбиение на нём невозможно
+            return Ok(());
+        };
+
+        let old_state = self.get_target(source, index);
+        let leaf = self.states[old_state].leaf;
+        let access_word = format!("{}{letter}", self.states[source].access_word);
+
+        let new_state = self.add_state(access_word, leaf);
+        let (old_leaf, new_leaf) =
+            self.tree
+                .split_leaf(leaf, discriminator, old_state, new_state, is_member);
+        self.states[old_state].leaf = old_leaf;
+        self.states[new_state].leaf = new_leaf;
+        self.transitions[source][index] = Transition::Tree(new_state);
+
+        Ok(())
+    }
+
+    // Ищет блок -- поддерево с временным различителем в корне, -- состояния
+    // которого различает окончательный различитель a v', где v' окончательно
+    // различает их a-преемников. Такой различитель заменяет корень блока.
+    fn finalize_discriminator(&mut self) -> Result<bool> {
+        let block_roots: Vec<usize> = (0..self.tree.nodes.len())
+            .filter(|node| self.tree.is_temporary(*node))
+            .filter(|node| {
+                let parent = self.tree.parents[*node].unwrap();
+                !self.tree.is_temporary(parent)
+            })
+            .collect();
+
+        for block_root in block_roots {
+            if let Some((index, separator)) = self.find_splitter(block_root) {
+                self.replace_block_root(block_root, index, separator)?;
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn find_splitter(&self, block_root: usize) -> Option<(usize, usize)> {
+        let [rejected, accepted] = self
+            .tree
+            .get_children(block_root)
+            .map(|child| self.tree.get_states(child));
+
+        let mut splitter: Option<(usize, usize)> = None;
+        for index in 0..self.alphabet.len() {
+            for first in &rejected {
+                for second in &accepted {
+                    let first_leaf = self.states[self.get_target(*first, index)].leaf;
+                    let second_leaf = self.states[self.get_target(*second, index)].leaf;
+                    if first_leaf == second_leaf {
+                        continue;
+                    }
+
+                    let separator = self
+                        .tree
+                        .get_lowest_common_ancestor(first_leaf, second_leaf);
+                    if self.tree.is_temporary(separator) {
+                        continue;
+                    }
+
+                    let length = self.tree.get_discriminator(separator).len();
+                    if splitter.is_none_or(|(_, best)| {
+                        length < self.tree.get_discriminator(best).len()
+                    }) {
+                        splitter = Some((index, separator));
+                    }
+                }
+            }
+        }
+
+        splitter
+    }
+
+    fn replace_block_root(&mut self, block_root: usize, index: usize, separator: usize) -> Result<()> {
+        let letter = self.alphabet[index];
+        let discriminator = format!("{letter}{}", self.tree.get_discriminator(separator));
+
+        // Ответы известны для преемников внутри поддерева v', для остальных
+        // состояний блока они запрашиваются
+        let mut sides: [Vec<usize>; 2] = [Vec::new(), Vec::new()];
+        for state in self.tree.get_states(block_root) {
+            let leaf = self.states[self.get_target(state, index)].leaf;
+            let known_outcome = self
+                .tree
+                .get_ancestors(leaf)
+                .into_iter()
+                .find(|(node, _)| *node == separator)
+                .map(|(_, outcome)| outcome);
+
+            let outcome = match known_outcome {
+                Some(outcome) => outcome,
+                None => {
+                    let access_word = &self.states[state].access_word;
+                    self.mat
+                        .check_membership(&format!("{access_word}{discriminator}"))?
+                }
+            };
+            sides[outcome as usize].push(state);
+        }
+
+        let parent = self.tree.parents[block_root].unwrap();
+        let new_root = self.tree.add_node(Node::Removed, Some(parent));
+        let children = [0, 1].map(|side| self.tree.extract(block_root, &sides[side], new_root));
+        self.tree.nodes[new_root] = Node::Inner {
+            discriminator,
+            is_final: true,
+            children,
+        };
+        if let Node::Inner { children, .. } = &mut self.tree.nodes[parent] {
+            for child in children.iter_mut().filter(|child| **child == block_root) {
+                *child = new_root;
+            }
+        }
+
+        // Слова переходов в удалённые узлы не проверялись новым различителем,
+        // поэтому они просеиваются заново от нового корня блока
+        let mut removed_nodes = Vec::new();
+        self.collect_nodes(block_root, &mut removed_nodes);
+        self.tree.remove_subtree(block_root);
+
+        for leaf in self.collect_leaves(new_root) {
+            if let Node::Leaf { state: Some(state) } = self.tree.nodes[leaf] {
+                self.states[state].leaf = leaf;
+            }
+        }
+
+        for transitions in &mut self.transitions {
+            for transition in transitions.iter_mut() {
+                if let Transition::NonTree(node) = *transition {
+                    if removed_nodes.contains(&node) {
+                        *transition = Transition::NonTree(new_root);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn collect_nodes(&self, node: usize, nodes: &mut Vec<usize>) {
+        nodes.push(node);
+        if let Node::Inner { children, .. } = &self.tree.nodes[node] {
+            for child in children {
+                self.collect_nodes(*child, nodes);
+            }
+        }
+    }
+
+    fn collect_leaves(&self, node: usize) -> Vec<usize> {
+        match &self.tree.nodes[node] {
+            Node::Leaf { .. } => vec![node],
+            Node::Inner { children, .. } => children
+                .iter()
+                .flat_map(|child| self.collect_leaves(*child))
+                .collect(),
+            Node::Removed => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mat::closure::ClosureMat;
+    use crate::mat::equivalence::EquivalenceMode;
+
+    // Каждый контрпример разбирается до конца раунда, так что следующая
+    // гипотеза классифицирует его верно
+    #[test]
+    fn uses_up_counterexample_within_round() {
+        let mat = ClosureMat::new(
+            "ab",
+            |word: &str| word.matches('a').count() % 5 == 2 || word.ends_with("bab"),
+            EquivalenceMode::Wp { extra_states: 3 },
+        );
+        let mut learner = TttImpl::new(&mat).unwrap();
+
+        let mut rounds = 0;
+        while let RoundResult::Counterexample(word) = learner.run_round().unwrap() {
+            let dfa = learner.build_dfa();
+            let is_member = mat.check_membership(&word).unwrap();
+            assert_eq!(dfa.check_membership(&word), is_member, "{word}");
+            rounds += 1;
+        }
+        assert!(rounds > 0);
+    }
+}
@@ -0,0 +1,197 @@
+use crate::automata::EPSILON;
+
+pub enum Node {
+    // Лист без состояния означает, что слов с таким поведением ещё не встречалось
+    Leaf {
+        state: Option<usize>,
+    },
+    // Дети упорядочены по ответу на запрос принадлежности: [нет, да]
+    Inner {
+        discriminator: String,
+        is_final: bool,
+        children: [usize; 2],
+    },
+    // Узел удалён при замене временного различителя окончательным
+    Removed,
+}
+
+pub struct DiscriminationTree {
+    pub nodes: Vec<Node>,
+    pub parents: Vec<Option<usize>>,
+}
+
+pub const ROOT: usize = 0;
+
+impl DiscriminationTree {
+    // Корень с окончательным различителем ε; состояние слова ε помещается в
+    // лист, соответствующий ответу на ε
+    pub fn new(start_state: usize, is_start_accepted: bool) -> Self {
+        let mut tree = Self {
+            nodes: Vec::new(),
+            parents: Vec::new(),
+        };
+
+        tree.add_node(
+            Node::Inner {
+                discriminator: EPSILON.to_owned(),
+                is_final: true,
+                children: [1, 2],
+            },
+            None,
+        );
+        for outcome in [false, true] {
+            let state = (outcome == is_start_accepted).then_some(start_state);
+            tree.add_node(Node::Leaf { state }, Some(ROOT));
+        }
+
+        tree
+    }
+
+    pub fn add_node(&mut self, node: Node, parent: Option<usize>) -> usize {
+        self.nodes.push(node);
+        self.parents.push(parent);
+        self.nodes.len() - 1
+    }
+
+    pub fn is_leaf(&self, node: usize) -> bool {
+        matches!(self.nodes[node], Node::Leaf { .. })
+    }
+
+    pub fn is_temporary(&self, node: usize) -> bool {
+        matches!(self.nodes[node], Node::Inner { is_final: false, .. })
+    }
+
+    pub fn get_discriminator(&self, node: usize) -> &str {
+        match &self.nodes[node] {
+            Node::Inner { discriminator, .. } => discriminator,
+            _ => panic!("node {node} is not an inner node"),
+        }
+    }
+
+    pub fn get_children(&self, node: usize) -> [usize; 2] {
+        match &self.nodes[node] {
+            Node::Inner { children, .. } => *children,
+            _ => panic!("node {node} is not an inner node"),
+        }
+    }
+
+    // Путь от узла к корню вместе с ответом, ведущим к узлу из каждого предка
+    pub fn get_ancestors(&self, node: usize) -> Vec<(usize, bool)> {
+        let mut ancestors = Vec::new();
+        let mut child = node;
+        while let Some(parent) = self.parents[child] {
+            let outcome = self.get_children(parent)[1] == child;
+            ancestors.push((parent, outcome));
+            child = parent;
+        }
+        ancestors
+    }
+
+    pub fn get_lowest_common_ancestor(&self, first: usize, second: usize) -> usize {
+        let first_ancestors: Vec<usize> = std::iter::once(first)
+            .chain(self.get_ancestors(first).into_iter().map(|(node, _)| node))
+            .collect();
+
+        std::iter::once(second)
+            .chain(self.get_ancestors(second).into_iter().map(|(node, _)| node))
+            .find(|node| first_ancestors.contains(node))
+            .unwrap()
+    }
+
+    pub fn get_states(&self, node: usize) -> Vec<usize> {
+        match &self.nodes[node] {
+            Node::Leaf { state } => state.iter().copied().collect(),
+            Node::Inner { children, .. } => children
+                .iter()
+                .flat_map(|child| self.get_states(*child))
+                .collect(),
+            Node::Removed => Vec::new(),
+        }
+    }
+
+    // Лист состояния превращается во временный внутренний узел
+    pub fn split_leaf(
+        &mut self,
+        leaf: usize,
+        discriminator: &str,
+        old_state: usize,
+        new_state: usize,
+        is_new_accepted: bool,
+    ) -> (usize, usize) {
+        let old_leaf = self.add_node(
+            Node::Leaf {
+                state: Some(old_state),
+            },
+            Some(leaf),
+        );
+        let new_leaf = self.add_node(
+            Node::Leaf {
+                state: Some(new_state),
+            },
+            Some(leaf),
+        );
+
+        let children = if is_new_accepted {
+            [old_leaf, new_leaf]
+        } else {
+            [new_leaf, old_leaf]
+        };
+        self.nodes[leaf] = Node::Inner {
+            discriminator: discriminator.to_owned(),
+            is_final: false,
+            children,
+        };
+
+        (old_leaf, new_leaf)
+    }
+
+    // Копирует поддерево, оставляя в нём только заданные состояния
+    pub fn extract(&mut self, node: usize, states: &[usize], parent: usize) -> usize {
+        match &self.nodes[node] {
+            Node::Leaf { state } => {
+                let state = *state;
+                self.add_node(Node::Leaf { state }, Some(parent))
+            }
+            Node::Inner {
+                discriminator,
+                is_final,
+                children,
+            } => {
+                let discriminator = discriminator.to_owned();
+                let is_final = *is_final;
+                let children = *children;
+
+                let has_states = children.map(|child| {
+                    self.get_states(child)
+                        .iter()
+                        .any(|state| states.contains(state))
+                });
+                if !has_states[0] {
+                    return self.extract(children[1], states, parent);
+                }
+                if !has_states[1] {
+                    return self.extract(children[0], states, parent);
+                }
+
+                let copy = self.add_node(Node::Removed, Some(parent));
+                let copied_children = children.map(|child| self.extract(child, states, copy));
+                self.nodes[copy] = Node::Inner {
+                    discriminator,
+                    is_final,
+                    children: copied_children,
+                };
+                copy
+            }
+            Node::Removed => panic!("node {node} is removed"),
+        }
+    }
+
+    pub fn remove_subtree(&mut self, node: usize) {
+        if let Node::Inner { children, .. } = &self.nodes[node] {
+            for child in *children {
+                self.remove_subtree(child);
+            }
+        }
+        self.nodes[node] = Node::Removed;
+    }
+}
@@ -0,0 +1,157 @@
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use lab3_solo::checkpoint::{Checkpoint, CheckpointConfig};
+use lab3_solo::error::Error;
+use lab3_solo::learner::{learn, resume, Algorithm, LearnerConfig, Limits, Status};
+use lab3_solo::mat::closure::ClosureMat;
+use lab3_solo::mat::equivalence::EquivalenceMode;
+
+// Слова с числом `a`, кратным трём, оканчивающиеся на `b`; эквивалентность
+// проверяется перебором, так что положение перебора тоже сохраняется
+fn get_mat() -> ClosureMat<impl Fn(&str) -> bool> {
+    ClosureMat::new(
+        "ab",
+        |word: &str| word.matches('a').count().is_multiple_of(3) && word.ends_with('b'),
+        EquivalenceMode::Shortlex {
+            max_tests: 10000,
+            word_max_len: 8,
+        },
+    )
+}
+
+fn get_path(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!(
+        "lab3_solo-checkpoint-{}-{name}.json",
+        std::process::id()
+    ));
+    path.display().to_string()
+}
+
+fn get_config(path: &str, interrupted: bool, limits: Limits) -> LearnerConfig {
+    LearnerConfig {
+        checkpoint: Some(CheckpointConfig {
+            path: path.to_owned(),
+            interval: 1,
+            interrupted: Arc::new(AtomicBool::new(interrupted)),
+        }),
+        limits,
+        ..Default::default()
+    }
+}
+
+// Запускает программу без терминала на stdin
+fn run_cli(checkpoint: &str, flags: &[&str]) -> std::process::Output {
+    std::process::Command::new(env!("CARGO_BIN_EXE_lab3_solo"))
+        .args(["ab", "(aaa|b)*ab", "0", "0", "--teacher", "regex"])
+        .args(["--checkpoint", checkpoint])
+        .args(flags)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn resumes_after_limit_to_same_hypothesis() {
+    let expected = learn(&LearnerConfig::default(), &get_mat()).unwrap();
+    assert!(expected.statistics.rounds > 1);
+
+    let path = get_path("limit");
+    let limits = Limits {
+        rounds: Some(1),
+        ..Default::default()
+    };
+    let stopped = learn(&get_config(&path, false, limits), &get_mat()).unwrap();
+    assert!(matches!(stopped.statistics.status, Status::LimitReached(_)));
+
+    let checkpoint = Checkpoint::load(&path).unwrap();
+    assert_eq!(checkpoint.rounds, 1);
+    let config = get_config(&path, false, Limits::default());
+    let resumed = resume(&config, &get_mat(), checkpoint).unwrap();
+
+    assert!(matches!(resumed.statistics.status, Status::Finished));
+    assert_eq!(resumed.hypothesis, expected.hypothesis);
+    assert_eq!(resumed.statistics.rounds, expected.statistics.rounds);
+    // Законченное обучение удаляет контрольную точку
+    assert!(!Path::new(&path).exists());
+}
+
+#[test]
+fn resumes_after_interruption_to_same_hypothesis() {
+    let expected = learn(&LearnerConfig::default(), &get_mat()).unwrap();
+
+    let path = get_path("interrupt");
+    let config = get_config(&path, true, Limits::default());
+    assert!(matches!(
+        learn(&config, &get_mat()),
+        Err(Error::Interrupted { path: saved_path }) if saved_path == path
+    ));
+
+    let checkpoint = Checkpoint::load(&path).unwrap();
+    assert_eq!(checkpoint.rounds, 1);
+    let config = get_config(&path, false, Limits::default());
+    let resumed = resume(&config, &get_mat(), checkpoint).unwrap();
+
+    assert_eq!(resumed.hypothesis, expected.hypothesis);
+    assert_eq!(resumed.statistics.rounds, expected.statistics.rounds);
+}
+
+#[test]
+fn rejects_checkpoint_of_other_alphabet() {
+    let path = get_path("alphabet");
+    let limits = Limits {
+        rounds: Some(1),
+        ..Default::default()
+    };
+    learn(&get_config(&path, false, limits), &get_mat()).unwrap();
+
+    let mut checkpoint = Checkpoint::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    checkpoint.alphabet = "xy".to_string();
+    assert!(matches!(
+        resume(&LearnerConfig::default(), &get_mat(), checkpoint),
+        Err(Error::Checkpoint(_))
+    ));
+}
+
+#[test]
+fn saves_checkpoints_only_for_nl() {
+    let config = LearnerConfig {
+        algorithm: Algorithm::LStar,
+        ..get_config(&get_path("lstar"), false, Limits::default())
+    };
+    assert!(matches!(
+        learn(&config, &get_mat()),
+        Err(Error::Checkpoint(_))
+    ));
+}
+
+#[test]
+fn requires_resume_or_restart_without_terminal() {
+    let path = get_path("cli");
+    assert!(run_cli(&path, &["--max-rounds", "1"]).status.success());
+    assert!(Path::new(&path).exists());
+
+    let output = run_cli(&path, &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already exists"));
+    assert!(Path::new(&path).exists());
+
+    assert!(!run_cli(&path, &["--resume", "--restart"]).status.success());
+
+    let restarted = run_cli(&path, &["--restart"]);
+    assert!(restarted.status.success());
+    assert!(!Path::new(&path).exists());
+
+    assert!(run_cli(&path, &["--max-rounds", "1"]).status.success());
+    let resumed = run_cli(&path, &["--resume"]);
+    assert!(resumed.status.success());
+    assert!(!Path::new(&path).exists());
+    // Продолженное обучение приходит к тому же автомату
+    assert_eq!(
+        String::from_utf8_lossy(&resumed.stdout).lines().next(),
+        String::from_utf8_lossy(&restarted.stdout).lines().next()
+    );
+}
@@ -0,0 +1,103 @@
+use lab3_solo::automata::{get_automata_impl, Automata, AutomataImpl};
+use lab3_solo::learner::{learn, Algorithm, LearnerConfig, Status};
+use lab3_solo::mat::reference::ReferenceMat;
+use lab3_solo::nl::counterexample::CounterexampleStrategy;
+
+const ALPHABET: &str = "ab";
+
+// Регулярные языки, на которых проверяются все алгоритмы
+const TARGETS: [&str; 6] = [
+    "∅",
+    "ε",
+    "(a|b)*",
+    "(a|b)*a(a|b)(a|b)",
+    "(aa|b)*",
+    "(ab|ba)*|a(a|b)*b",
+];
+
+const STRATEGIES: [CounterexampleStrategy; 4] = [
+    CounterexampleStrategy::AllPrefixes,
+    CounterexampleStrategy::AllSuffixes,
+    CounterexampleStrategy::RivestSchapire,
+    CounterexampleStrategy::MalerPnueli,
+];
+
+fn get_minimal_dfa(regex: &str) -> AutomataImpl {
+    let automata = lab3_solo::regex::parse(regex).unwrap();
+    let dfa = automata.determinize();
+    get_automata_impl(dfa.as_ref()).minimize(ALPHABET)
+}
+
+// Обучается языку каждого регулярного выражения и возвращает гипотезу
+// вместе с минимальным ДКА целевого языка
+fn learn_targets(config: &LearnerConfig) -> Vec<(AutomataImpl, AutomataImpl)> {
+    TARGETS
+        .iter()
+        .map(|regex| {
+            let mat = ReferenceMat::from_regex(ALPHABET, regex).unwrap();
+            let result = learn(config, &mat).unwrap();
+            assert!(
+                matches!(result.statistics.status, Status::Finished),
+                "{regex}"
+            );
+            (result.hypothesis, get_minimal_dfa(regex))
+        })
+        .collect()
+}
+
+fn check_algorithm(algorithm: Algorithm) {
+    let config = LearnerConfig {
+        algorithm,
+        ..Default::default()
+    };
+
+    for (hypothesis, target) in learn_targets(&config) {
+        // L*, KV и TTT строят минимальный полный ДКА
+        assert_eq!(hypothesis.size, target.size);
+        assert_eq!(hypothesis.minimize(ALPHABET), target);
+    }
+}
+
+fn check_strategy(counterexample_strategy: CounterexampleStrategy, shorten: bool) {
+    let config = LearnerConfig {
+        algorithm: Algorithm::Nl,
+        counterexample_strategy,
+        shorten_counterexamples: shorten,
+        ..Default::default()
+    };
+
+    // ДКА, полученный из RFSA, может быть не минимальным, но задаёт тот же язык
+    for (hypothesis, target) in learn_targets(&config) {
+        assert_eq!(hypothesis.find_distinguishing_word(&target, ALPHABET), None);
+        assert_eq!(hypothesis.minimize(ALPHABET), target);
+    }
+}
+
+#[test]
+fn lstar_learns_minimal_dfa() {
+    check_algorithm(Algorithm::LStar);
+}
+
+#[test]
+fn kv_learns_minimal_dfa() {
+    check_algorithm(Algorithm::Kv);
+}
+
+#[test]
+fn ttt_learns_minimal_dfa() {
+    check_algorithm(Algorithm::Ttt);
+}
+
+#[test]
+fn nl_learns_target_with_every_counterexample_strategy() {
+    for strategy in STRATEGIES {
+        check_strategy(strategy, false);
+    }
+}
+
+#[test]
+fn nl_learns_target_with_shortened_counterexamples() {
+    for strategy in STRATEGIES {
+        check_strategy(strategy, true);
+    }
+}
@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use lab3_solo::learner::{learn, Algorithm, LearnerConfig, LearningResult, Limit, Limits, Status};
+use lab3_solo::mat::closure::ClosureMat;
+use lab3_solo::mat::equivalence::EquivalenceMode;
+use lab3_solo::mat::reference::ReferenceMat;
+
+// Минимальный ДКА из 16 состояний: без ограничений NL* делает два раунда
+const TARGET: &str = "(a|b)*a(a|b)(a|b)(a|b)";
+
+fn learn_with(algorithm: Algorithm, limits: Limits) -> LearningResult {
+    let mat = ReferenceMat::from_regex("ab", TARGET).unwrap();
+    let config = LearnerConfig {
+        algorithm,
+        limits,
+        ..Default::default()
+    };
+    learn(&config, &mat).unwrap()
+}
+
+fn get_limit(result: &LearningResult) -> Option<Limit> {
+    match result.statistics.status {
+        Status::Finished => None,
+        Status::LimitReached(limit) => Some(limit),
+    }
+}
+
+#[test]
+fn finishes_without_limits() {
+    let result = learn_with(Algorithm::Nl, Limits::default());
+    assert!(get_limit(&result).is_none());
+    assert!(result.diagnostics.is_none());
+}
+
+#[test]
+fn stops_at_membership_query_limit() {
+    for algorithm in [
+        Algorithm::Nl,
+        Algorithm::LStar,
+        Algorithm::Kv,
+        Algorithm::Ttt,
+    ] {
+        let limits = Limits {
+            membership_queries: Some(20),
+            ..Default::default()
+        };
+        let result = learn_with(algorithm, limits);
+        assert!(matches!(get_limit(&result), Some(Limit::MembershipQueries)));
+        assert!(result.statistics.membership_queries <= 20);
+        assert!(result.diagnostics.is_some());
+    }
+}
+
+#[test]
+fn stops_at_round_limit() {
+    let limits = Limits {
+        rounds: Some(1),
+        ..Default::default()
+    };
+    let result = learn_with(Algorithm::Nl, limits);
+    assert!(matches!(get_limit(&result), Some(Limit::Rounds)));
+    assert_eq!(result.statistics.rounds, 1);
+    assert_eq!(result.statistics.equivalence_queries, 1);
+    // Результатом служит последняя предъявленная гипотеза
+    assert_eq!(
+        result.hypothesis.size,
+        result.statistics.hypothesis_sizes[0]
+    );
+}
+
+#[test]
+fn stops_at_state_limit() {
+    let limits = Limits {
+        states: Some(4),
+        ..Default::default()
+    };
+    let result = learn_with(Algorithm::Nl, limits);
+    assert!(matches!(get_limit(&result), Some(Limit::States)));
+    // Слишком большая гипотеза учителю не предъявляется
+    assert!(result
+        .statistics
+        .hypothesis_sizes
+        .iter()
+        .all(|size| *size <= 4));
+}
+
+#[test]
+fn stops_at_time_limit() {
+    let limits = Limits {
+        time: Some(Duration::ZERO),
+        ..Default::default()
+    };
+    let result = learn_with(Algorithm::Nl, limits);
+    assert!(matches!(get_limit(&result), Some(Limit::Time)));
+    assert_eq!(result.statistics.membership_queries, 0);
+}
+
+#[test]
+fn stops_when_hypothesis_keeps_growing() {
+    // a^n b^n нерегулярен: каждый контрпример добавляет гипотезе состояния
+    let mat = ClosureMat::new(
+        "ab",
+        |word: &str| {
+            let count = word.chars().take_while(|letter| *letter == 'a').count();
+            word.len() == 2 * count && word[count..].chars().all(|letter| letter == 'b')
+        },
+        EquivalenceMode::Shortlex {
+            max_tests: 100000,
+            word_max_len: 14,
+        },
+    );
+    let config = LearnerConfig {
+        limits: Limits {
+            growing_rounds: Some(3),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let result = learn(&config, &mat).unwrap();
+    assert!(matches!(get_limit(&result), Some(Limit::Growth)));
+
+    let diagnostics = result.diagnostics.unwrap();
+    assert!(diagnostics.growing_rounds >= 3);
+    // Префиксы a^0, a^1, ... попарно различимы суффиксами из b
+    let family = &diagnostics.families[0];
+    assert_eq!(family.base, "a");
+    assert!(family.prefixes.len() >= 3);
+    assert!(family.witnesses.iter().all(Option::is_some));
+    assert!(diagnostics.lower_bound >= family.prefixes.len());
+}